@@ -1,4 +1,4 @@
-use generative_ecs_2::arenas::Arena;
+use generative_ecs_2::arenas::{Arena, ReferentialAction};
 use generative_ecs_2::entities::{Entity, EntityEnum};
 use generative_ecs_2::lifespans::*;
 use generative_ecs_2::worlds::{World, Insert};
@@ -8,12 +8,48 @@ use generative_ecs_2::worlds::{World, Insert};
 fn main() {
     let target = "./examples/target.rs";
 
-    let addition = r#"#[derive(Debug, Default, Copy, Clone)]
+    std::fs::write(target, generate()).ok();
+}
+
+/// The generated world followed by the hand-written code it relies on, as written to target.rs.
+pub fn generate() -> String {
+    get_world().to_string() + ADDITION
+}
+
+const ADDITION: &str = r#"#[derive(Debug, Default, Copy, Clone)]
 pub struct Population;
 #[derive(Debug, Default, Copy, Clone)]
 pub struct Albedo(f64);
 #[derive(Debug, Default, Copy, Clone)]
 pub struct Starfield;
+#[derive(Debug, Default, Clone)]
+pub struct Atmosphere(Vec<(String, f64)>);
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Absorption(f64);
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Flag(pub char);
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Registry(pub String);
+
+fn get_absorption(albedo: &Albedo) -> Absorption {
+    Absorption(1.0 - albedo.0)
+}
+
+fn get_orbit_position(relative_position: &Position, parent: Option<&Position>) -> Position {
+    let mut position = *relative_position;
+    if let Some(parent) = parent {
+        position += *parent;
+    }
+    position
+}
+
+fn get_orbit_depth(parent: Option<&u32>) -> u32 {
+    parent.map_or(0, |depth| depth + 1)
+}
+
+fn get_body_position(orbit: Option<&Position>) -> Position {
+    orbit.copied().unwrap_or_default()
+}
 
 fn main() {
     let mut world = World::default();
@@ -21,6 +57,8 @@ fn main() {
     let sol = world.create_system(get_sol());
     let earth = world.create_body(get_earth(sol));
     let _moon = world.create_body(get_luna(&world.state, earth));
+
+    world.update_derived();
 }
 
 fn get_sol() -> SystemRow {
@@ -48,7 +86,11 @@ fn get_earth(system: Id<System>) -> BodyEntity {
         surface: Some(SurfaceRow {
             area: Area::in_meters_squared(510.1e12),
             albedo: Albedo(0.30),
+            atmosphere: Some(Atmosphere(vec![("N2".to_string(), 0.78), ("O2".to_string(), 0.21)])),
         }),
+        mine_site: vec![MineSiteRow {
+            capacity: Mass::in_kilograms(1e9),
+        }],
     }
 }
 
@@ -70,42 +112,14 @@ fn get_luna(state: &State, earth: Id<Body>) -> BodyEntity {
         surface: Some(SurfaceRow {
             area: Area::in_meters_squared(38e12),
             albedo: Albedo(0.12),
+            atmosphere: None,
         }),
-    }
-}
-
-pub struct BodyPosition;
-
-impl BodyPosition {
-    pub fn update(state: &mut State) {
-        let position = &mut state.body.position;
-        let orbit = &state.body.orbit;
-
-        let relative_position = &state.orbit.relative_position;
-        let parent = &state.orbit.parent;
-
-        position
-            .iter_mut()
-            .zip(orbit.iter())
-            .filter_map(|(pos, orbit)| orbit.map(|orbit| (pos, orbit)))
-            .for_each(|(pos, orbit)| {
-                let mut orbit = orbit;
-                *pos = relative_position[orbit];
-                while let Some(parent) = parent[orbit] {
-                    orbit = parent;
-                    *pos += relative_position[orbit];
-                }
-            })
+        mine_site: vec![],
     }
 }
 
 "#;
 
-    let world = get_world();
-
-    std::fs::write(target, world.to_string() + addition).ok();
-}
-
 pub fn get_world() -> World {
     let mut system = Arena::<Permanent>::new("System");
     system.add_optional_component_with_field("name", "String");
@@ -118,7 +132,7 @@ pub fn get_world() -> World {
     body.add_optional_component_with_field("name", "String");
     body.add_required_component("Mass");
     body.add_required_component_with_field("radius", "Length");
-    body.add_default_component("Position");
+    body.add_derived_component("Position", "get_body_position", &["orbit.position"]);
     body.add_default_component("Velocity");
 
     let mut orbit = Arena::<Permanent>::new("Orbit");
@@ -126,30 +140,63 @@ pub fn get_world() -> World {
     orbit.add_required_component_with_field("period", "Time");
     orbit.add_required_component_with_field("radius", "Length");
     orbit.add_default_component_with_field("relative_position", "Position");
+    orbit.add_derived_component("Position", "get_orbit_position", &["relative_position", "parent.position"]);
+    orbit.add_derived_component_with_field("depth", "u32", "get_orbit_depth", &["parent.depth"]);
 
     let mut surface = Arena::<Permanent>::new("Surface");
     surface.add_required_component("Area");
     surface.add_required_component("Albedo");
-    surface.add_default_component("Temperature");
+    surface.add_default_component_with_expr("temperature", "Temperature", "Temperature::in_kelvin(3.0)");
+    surface.add_rare_component("Atmosphere");
+    surface.add_derived_component("Absorption", "get_absorption", &["albedo"]);
+
+    let mut mine_site = Arena::<Permanent>::new("MineSite");
+    mine_site.add_required_component_with_field("capacity", "Mass");
 
     let mut nation = Arena::<Transient>::new("Nation");
     nation.add_required_component_with_field("name", "String");
+    nation.set_clearable("name");
+    nation.add_required_component("Flag");
     nation.add_default_component("Population");
 
     let mut colony = Arena::<Transient>::new("Colony");
     colony.add_reference(&body);
-    colony.add_reference(&nation);
+    colony.add_owner_reference(&nation);
     colony.add_required_component_with_field("name", "String");
+    colony.set_clearable("name");
     colony.add_required_component("Population");
+    colony.add_tag("is_capital");
+
+    let mut trade_route = Arena::<Transient>::new("TradeRoute");
+    trade_route.add_required_component_with_field("distance", "Length");
+    trade_route.add_many_reference_with_field("colonies", &colony);
 
     let mut vessel = Arena::<Transient>::new("Vessel");
     vessel.add_required_component_with_field("name", "String");
     vessel.add_required_component("Mass");
     vessel.add_required_component("Speed");
+    vessel.add_default_component_with_init("registry", "Registry", "|row| Registry(row.name.to_uppercase())");
+    vessel.set_clearable("name");
+    vessel.set_clearable("registry");
+    vessel.add_late_reference_with_field("target", &body);
+    vessel.add_late_reference_with_field("home", &colony);
 
     let mut engine = Arena::<Transient>::new("Engine");
     engine.add_required_component_with_field("thrust", "Force");
 
+    let mut cargo_pod = Arena::<Transient>::new("CargoPod");
+    cargo_pod.add_required_component_with_field("cargo", "Mass");
+
+    colony.add_optional_reference_with_field("supply_pod", &cargo_pod);
+
+    let mut contract = Arena::<Transient>::new("Contract");
+    contract.add_owner_reference_with_field("pod", &cargo_pod);
+    contract.add_required_component_with_field("payment", "f64");
+
+    let mut repair_order = Arena::<Transient>::new("RepairOrder");
+    repair_order.add_optional_reference(&engine);
+    repair_order.set_referential_action("engine", ReferentialAction::Restrict);
+
     let mut vessel_transit = Arena::<Transient>::new("VesselTransit");
     vessel_transit.add_required_component_with_field("departure", "Time");
     vessel_transit.add_required_component_with_field("arrival", "Time");
@@ -164,9 +211,11 @@ pub fn get_world() -> World {
     let mut planet = Entity::new(&body);
     planet.add_child(&orbit);
     planet.add_child(&surface);
+    planet.add_collection(&mine_site);
 
     let mut vessel_entity = Entity::new(&vessel);
     vessel_entity.add_child(&engine);
+    vessel_entity.add_collection(&cargo_pod);
     vessel_entity.add_enum(EntityEnum::new("VesselLocation", vec![&vessel_orbit, &vessel_transit]));
 
     let mut world = World::new();
@@ -179,10 +228,15 @@ pub fn get_world() -> World {
     world.insert(body);
     world.insert(orbit);
     world.insert(surface);
+    world.insert(mine_site);
     world.insert(nation);
     world.insert(colony);
+    world.insert(trade_route);
     world.insert(vessel);
     world.insert(engine);
+    world.insert(cargo_pod);
+    world.insert(contract);
+    world.insert(repair_order);
     world.insert(vessel_transit);
     world.insert(vessel_orbit);
 
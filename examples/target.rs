@@ -16,10 +16,12 @@ impl World {
         let (alloc, state) = self.split();
         
         let id = state.body.create(entity.body, &mut alloc.body);
+        state.index_body(&id);
 
         if let Some(orbit) = entity.orbit {
             let orbit = state.orbit.create(orbit, &mut alloc.orbit);
             state.link_body_to_orbit(&id, &orbit);
+            state.index_orbit(&orbit);
         }
 
         if let Some(surface) = entity.surface {
@@ -27,6 +29,11 @@ impl World {
             state.link_body_to_surface(&id, &surface);
         }
 
+        for mine_site in entity.mine_site {
+            let mine_site = state.mine_site.create(mine_site, &mut alloc.mine_site);
+            state.add_mine_site_to_body(&id, &mine_site);
+        }
+
         
         id
     }
@@ -35,42 +42,277 @@ impl World {
         let (alloc, state) = self.split();
         
         let id = state.vessel.create(entity.vessel, &mut alloc.vessel);
+        state.index_vessel(&id);
 
         if let Some(engine) = entity.engine {
             let engine = state.engine.create(engine, &mut alloc.engine);
             state.link_vessel_to_engine(&id, &engine);
         }
 
+        for cargo_pod in entity.cargo_pod {
+            let cargo_pod = state.cargo_pod.create(cargo_pod, &mut alloc.cargo_pod);
+            state.add_cargo_pod_to_vessel(&id, &cargo_pod);
+        }
+
         match entity.vessel_location {
             VesselLocationRow::VesselOrbit(row) => {
                 let vessel_orbit = state.vessel_orbit.create(row, &mut alloc.vessel_orbit);
                 state.link_vessel_to_vessel_orbit(&id, &vessel_orbit);
+                state.index_vessel_orbit(&vessel_orbit);
             }
             VesselLocationRow::VesselTransit(row) => {
                 let vessel_transit = state.vessel_transit.create(row, &mut alloc.vessel_transit);
                 state.link_vessel_to_vessel_transit(&id, &vessel_transit);
+                state.index_vessel_transit(&vessel_transit);
             }
         }
         
         id
     }
 
-    pub fn delete_vessel(&mut self, id: GenId<Vessel>) {
+    pub fn delete_vessel(&mut self, id: GenId<Vessel>) -> Result<(), DeleteRestricted> {
         let (alloc, state) = self.split();
 
+        let mut cargo_pod_contract_pod = vec![];
+        
         if let Some(id) = alloc.vessel.validate(id) {
-            if let Some(child) = state.vessel.engine.get_opt(&id) {
-                alloc.engine.kill(*child);
+            state.can_delete_vessel(&id)?;
+
+            if let Some(child) = state.vessel.engine.get_opt(&id).copied() {
+                state.engine.remove(&Valid::new(child));
+                alloc.engine.kill(child);
+            }
+
+            for child in std::mem::take(&mut state.vessel.cargo_pod[&id]) {
+                for referrer in state.cargo_pod.colony_supply_pod[&Valid::new(child)].iter() {
+                    state.colony.supply_pod[&Valid::new(*referrer)] = None;
+                }
+
+                cargo_pod_contract_pod.extend(std::mem::take(&mut state.cargo_pod.contract_pod[&Valid::new(child)]));
+
+                state.cargo_pod.remove(&Valid::new(child));
+                alloc.cargo_pod.kill(child);
             }
 
-            match state.vessel.vessel_location.get(&id) {
-                Some(VesselLocation::VesselOrbit(child)) => alloc.vessel_orbit.kill(*child),
-                Some(VesselLocation::VesselTransit(child)) => alloc.vessel_transit.kill(*child),
+            match state.vessel.vessel_location.get(&id).copied() {
+                Some(VesselLocation::VesselOrbit(child)) => {
+                    state.unindex_vessel_orbit(&Valid::new(child));
+                    state.vessel_orbit.remove(&Valid::new(child));
+                    alloc.vessel_orbit.kill(child);
+                }
+                Some(VesselLocation::VesselTransit(child)) => {
+                    state.unindex_vessel_transit(&Valid::new(child));
+                    state.vessel_transit.remove(&Valid::new(child));
+                    alloc.vessel_transit.kill(child);
+                }
                 None => {},
             }
+
+            state.unindex_vessel(&id);
+            state.vessel.remove(&id);
         }
 
         alloc.vessel.kill(id);
+
+        for id in cargo_pod_contract_pod {
+            self.delete_contract(id)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn get_body(&self, id: Id<Body>) -> BodyEntity {
+        let state = &self.state;
+
+        BodyEntity {
+            body: state.get_body_row(&id),
+            orbit: state.body.orbit[&id].as_ref().map(|c| state.get_orbit_row(c)),
+            surface: state.body.surface[&id].as_ref().map(|c| state.get_surface_row(c)),
+            mine_site: state.body.mine_site[&id].iter().map(|c| state.get_mine_site_row(c)).collect(),
+        }
+    }
+
+    pub fn get_vessel(&self, id: GenId<Vessel>) -> Option<VesselEntity> {
+        let id = self.allocators.vessel.validate(id)?;
+        let state = &self.state;
+
+        Some(VesselEntity {
+            vessel: state.get_vessel_row(&id),
+            engine: state.vessel.engine[&id].as_ref().map(|c| state.get_engine_row(&Valid::new(*c))),
+            cargo_pod: state.vessel.cargo_pod[&id].iter().map(|c| state.get_cargo_pod_row(&Valid::new(*c))).collect(),
+            vessel_location: match &state.vessel.vessel_location[&id] {
+                VesselLocation::VesselOrbit(c) => VesselLocationRow::VesselOrbit(state.get_vessel_orbit_row(&Valid::new(*c))),
+                VesselLocation::VesselTransit(c) => VesselLocationRow::VesselTransit(state.get_vessel_transit_row(&Valid::new(*c))),
+            },
+        })
+    }
+
+    pub fn attach_orbit_to_body(&mut self, id: Id<Body>, row: OrbitRow) -> Result<Id<Orbit>, OrbitRow> {
+        let (alloc, state) = self.split();
+
+        if state.body.orbit[&id].is_some() {
+            return Err(row);
+        }
+
+        let orbit = state.orbit.create(row, &mut alloc.orbit);
+        state.link_body_to_orbit(&id, &orbit);
+        state.index_orbit(&orbit);
+
+        Ok(orbit)
+    }
+
+    pub fn attach_surface_to_body(&mut self, id: Id<Body>, row: SurfaceRow) -> Result<Id<Surface>, SurfaceRow> {
+        let (alloc, state) = self.split();
+
+        if state.body.surface[&id].is_some() {
+            return Err(row);
+        }
+
+        let surface = state.surface.create(row, &mut alloc.surface);
+        state.link_body_to_surface(&id, &surface);
+
+        Ok(surface)
+    }
+
+    pub fn attach_engine_to_vessel(&mut self, id: GenId<Vessel>, row: EngineRow) -> Result<Valid<Engine>, EngineRow> {
+        let (alloc, state) = self.split();
+
+        let id = match alloc.vessel.validate(id) {
+            Some(id) => id,
+            None => return Err(row),
+        };
+
+        if state.vessel.engine[&id].is_some() {
+            return Err(row);
+        }
+
+        let engine = state.engine.create(row, &mut alloc.engine);
+        state.link_vessel_to_engine(&id, &engine);
+
+        Ok(engine)
+    }
+
+    pub fn detach_engine_from_vessel(&mut self, id: GenId<Vessel>) -> Result<Option<GenId<Engine>>, DeleteRestricted> {
+        let (alloc, state) = self.split();
+
+        let id = match alloc.vessel.validate(id) {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        let child = match state.vessel.engine[&id] {
+            Some(child) => child,
+            None => return Ok(None),
+        };
+
+        state.can_delete_engine(&Valid::new(child))?;
+        state.vessel.engine[&id] = None;
+
+        state.engine.remove(&Valid::new(child));
+        alloc.engine.kill(child);
+        
+        Ok(Some(child))
+    }
+
+    pub fn remove_cargo_pod_from_vessel(&mut self, id: GenId<Vessel>, child: GenId<CargoPod>) -> Result<Option<GenId<CargoPod>>, DeleteRestricted> {
+        let (alloc, state) = self.split();
+
+        let mut cargo_pod_contract_pod = vec![];
+        
+        let id = match alloc.vessel.validate(id) {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        if !state.vessel.cargo_pod[&id].contains(&child) {
+            return Ok(None);
+        }
+
+        state.vessel.cargo_pod[&id].retain(|c| *c != child);
+
+        for referrer in state.cargo_pod.colony_supply_pod[&Valid::new(child)].iter() {
+            state.colony.supply_pod[&Valid::new(*referrer)] = None;
+        }
+
+        cargo_pod_contract_pod.extend(std::mem::take(&mut state.cargo_pod.contract_pod[&Valid::new(child)]));
+
+        state.cargo_pod.remove(&Valid::new(child));
+        alloc.cargo_pod.kill(child);
+        
+        for id in cargo_pod_contract_pod {
+            self.delete_contract(id)?;
+        }
+
+        Ok(Some(child))
+    }
+
+    pub fn create_vessel_location(&mut self, id: GenId<Vessel>, row: VesselLocationRow) -> Option<VesselLocation> {
+        let (alloc, state) = self.split();
+
+        let id = alloc.vessel.validate(id)?;
+
+        if state.vessel.vessel_location.get(&id).is_some() {
+            return None;
+        }
+
+        let child = match row {
+            VesselLocationRow::VesselOrbit(row) => {
+                let vessel_orbit = state.vessel_orbit.create(row, &mut alloc.vessel_orbit);
+                state.link_vessel_to_vessel_orbit(&id, &vessel_orbit);
+                state.index_vessel_orbit(&vessel_orbit);
+                VesselLocation::from(vessel_orbit)
+            }
+            VesselLocationRow::VesselTransit(row) => {
+                let vessel_transit = state.vessel_transit.create(row, &mut alloc.vessel_transit);
+                state.link_vessel_to_vessel_transit(&id, &vessel_transit);
+                state.index_vessel_transit(&vessel_transit);
+                VesselLocation::from(vessel_transit)
+            }
+        };
+
+        Some(child)
+    }
+
+    pub fn set_vessel_location(&mut self, id: GenId<Vessel>, row: VesselLocationRow) -> Result<Option<VesselLocation>, DeleteRestricted> {
+        let (alloc, state) = self.split();
+
+        let id = match alloc.vessel.validate(id) {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+
+        let old = state.vessel.vessel_location.get(&id).copied();
+
+        match old {
+            Some(VesselLocation::VesselOrbit(child)) => {
+                state.unindex_vessel_orbit(&Valid::new(child));
+                state.vessel_orbit.remove(&Valid::new(child));
+                alloc.vessel_orbit.kill(child);
+            }
+            Some(VesselLocation::VesselTransit(child)) => {
+                state.unindex_vessel_transit(&Valid::new(child));
+                state.vessel_transit.remove(&Valid::new(child));
+                alloc.vessel_transit.kill(child);
+            }
+            None => {},
+        }
+
+        let child = match row {
+            VesselLocationRow::VesselOrbit(row) => {
+                let vessel_orbit = state.vessel_orbit.create(row, &mut alloc.vessel_orbit);
+                state.link_vessel_to_vessel_orbit(&id, &vessel_orbit);
+                state.index_vessel_orbit(&vessel_orbit);
+                VesselLocation::from(vessel_orbit)
+            }
+            VesselLocationRow::VesselTransit(row) => {
+                let vessel_transit = state.vessel_transit.create(row, &mut alloc.vessel_transit);
+                state.link_vessel_to_vessel_transit(&id, &vessel_transit);
+                state.index_vessel_transit(&vessel_transit);
+                VesselLocation::from(vessel_transit)
+            }
+        };
+
+        Ok(Some(child))
     }
 
     pub fn create_system(&mut self, row: SystemRow) -> Id<System> {
@@ -88,8 +330,112 @@ impl World {
     pub fn create_colony(&mut self, row: ColonyRow) -> Valid<Colony> {
         let id = self.allocators.colony.create();
         self.state.colony.insert(&id, row);
+        self.state.index_colony(&id);
+        id
+    }
+
+    pub fn create_trade_route(&mut self, row: TradeRouteRow) -> Valid<TradeRoute> {
+        let id = self.allocators.trade_route.create();
+        self.state.trade_route.insert(&id, row);
+        id
+    }
+
+    pub fn create_contract(&mut self, row: ContractRow) -> Valid<Contract> {
+        let id = self.allocators.contract.create();
+        self.state.contract.insert(&id, row);
+        self.state.index_contract(&id);
+        id
+    }
+
+    pub fn create_repair_order(&mut self, row: RepairOrderRow) -> Valid<RepairOrder> {
+        let id = self.allocators.repair_order.create();
+        self.state.repair_order.insert(&id, row);
+        self.state.index_repair_order(&id);
         id
     }
+
+    pub fn delete_nation(&mut self, id: GenId<Nation>) -> Result<(), DeleteRestricted> {
+        let (alloc, state) = self.split();
+
+        let mut nation_colony = vec![];
+        
+        if let Some(id) = alloc.nation.validate(id) {
+            nation_colony.extend(std::mem::take(&mut state.nation.colony[&id]));
+
+            state.nation.remove(&id);
+        }
+
+        alloc.nation.kill(id);
+
+        for id in nation_colony {
+            self.delete_colony(id)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn delete_colony(&mut self, id: GenId<Colony>) -> Result<(), DeleteRestricted> {
+        let (alloc, state) = self.split();
+
+        if let Some(id) = alloc.colony.validate(id) {
+            for referrer in state.colony.vessel_home[&id].iter() {
+                state.vessel.home[&Valid::new(*referrer)] = None;
+            }
+
+            state.unindex_colony(&id);
+            state.prune_colony_links(&id);
+            state.colony.remove(&id);
+        }
+
+        alloc.colony.kill(id);
+
+        Ok(())
+    }
+
+    pub fn delete_trade_route(&mut self, id: GenId<TradeRoute>) -> Result<(), DeleteRestricted> {
+        let (alloc, state) = self.split();
+
+        if let Some(id) = alloc.trade_route.validate(id) {
+            state.prune_trade_route_links(&id);
+            state.trade_route.remove(&id);
+        }
+
+        alloc.trade_route.kill(id);
+
+        Ok(())
+    }
+
+    pub fn delete_contract(&mut self, id: GenId<Contract>) -> Result<(), DeleteRestricted> {
+        let (alloc, state) = self.split();
+
+        if let Some(id) = alloc.contract.validate(id) {
+            state.unindex_contract(&id);
+            state.contract.remove(&id);
+        }
+
+        alloc.contract.kill(id);
+
+        Ok(())
+    }
+
+    pub fn delete_repair_order(&mut self, id: GenId<RepairOrder>) -> Result<(), DeleteRestricted> {
+        let (alloc, state) = self.split();
+
+        if let Some(id) = alloc.repair_order.validate(id) {
+            state.unindex_repair_order(&id);
+            state.repair_order.remove(&id);
+        }
+
+        alloc.repair_order.kill(id);
+
+        Ok(())
+    }
+
+    pub fn update_derived(&mut self) {
+        self.state.update_orbit(&self.allocators.orbit);
+        self.state.update_body(&self.allocators.body);
+        self.state.update_surface();
+    }
 }
 
 
@@ -99,10 +445,15 @@ pub struct Allocators {
     pub body: FixedAllocator<Body>,
     pub orbit: FixedAllocator<Orbit>,
     pub surface: FixedAllocator<Surface>,
+    pub mine_site: FixedAllocator<MineSite>,
     pub nation: GenAllocator<Nation>,
     pub colony: GenAllocator<Colony>,
+    pub trade_route: GenAllocator<TradeRoute>,
     pub vessel: GenAllocator<Vessel>,
     pub engine: GenAllocator<Engine>,
+    pub cargo_pod: GenAllocator<CargoPod>,
+    pub contract: GenAllocator<Contract>,
+    pub repair_order: GenAllocator<RepairOrder>,
     pub vessel_transit: GenAllocator<VesselTransit>,
     pub vessel_orbit: GenAllocator<VesselOrbit>,
 }
@@ -114,10 +465,15 @@ pub struct State {
     pub body: Body,
     pub orbit: Orbit,
     pub surface: Surface,
+    pub mine_site: MineSite,
     pub nation: Nation,
     pub colony: Colony,
+    pub trade_route: TradeRoute,
     pub vessel: Vessel,
     pub engine: Engine,
+    pub cargo_pod: CargoPod,
+    pub contract: Contract,
+    pub repair_order: RepairOrder,
     pub vessel_transit: VesselTransit,
     pub vessel_orbit: VesselOrbit,
 }
@@ -147,197 +503,1501 @@ impl State {
         self.vessel.vessel_location.insert(vessel, vessel_transit.id().into());
         self.vessel_transit.vessel.insert(vessel_transit, vessel.id());
     }
-}
 
+    pub fn add_mine_site_to_body(&mut self, body: &Id<Body>, mine_site: &Id<MineSite>) {
+        self.body.mine_site[body].push(mine_site.id());
+        self.mine_site.body.insert(mine_site, body.id());
+    }
 
-#[derive(Debug, Default, Clone)]
-pub struct System {
-    pub name: Component<Self, Option<String>>,
-    pub position: Component<Self, Position>,
-    pub temperature: Component<Self, Temperature>,
-    pub radius: Component<Self, Length>,
-}
+    pub fn add_cargo_pod_to_vessel(&mut self, vessel: &Valid<Vessel>, cargo_pod: &Valid<CargoPod>) {
+        self.vessel.cargo_pod[vessel].push(cargo_pod.id());
+        self.cargo_pod.vessel.insert(cargo_pod, vessel.id());
+    }
 
-impl System {
-    pub fn insert(&mut self, id: &Id<System>, row: SystemRow) {
-        self.name.insert(id, row.name);
-        self.position.insert(id, row.position);
-        self.temperature.insert(id, row.temperature);
-        self.radius.insert(id, row.radius);
+    pub fn index_body(&mut self, id: &Id<Body>) {
+        let system = self.body.system[id];
+        self.system.body[system].push(id.id());
     }
 
-    pub fn create<'a>(&mut self, row: SystemRow, alloc: &'a mut FixedAllocator<System>) -> Id<System> {
-        let id = alloc.create();
-        self.insert(&id, row);
-        id
+    pub fn unindex_body(&mut self, id: &Id<Body>) {
+        let system = self.body.system[id];
+        self.system.body[system].retain(|r| *r != id.id());
     }
-}
 
+    pub fn set_body_system(&mut self, id: &Id<Body>, value: Id<System>) {
+        let system = self.body.system[id];
+        self.system.body[system].retain(|r| *r != id.id());
+        self.body.system[id] = value;
+        let system = self.body.system[id];
+        self.system.body[system].push(id.id());
+    }
 
-#[derive(Debug, Default, Clone)]
-pub struct Body {
-    pub system: Component<Self, Id<System>>,
-    pub name: Component<Self, Option<String>>,
-    pub mass: Component<Self, Mass>,
-    pub radius: Component<Self, Length>,
-    pub position: Component<Self, Position>,
-    pub velocity: Component<Self, Velocity>,
-    pub orbit: Component<Self, Option<Id<Orbit>>>,
-    pub surface: Component<Self, Option<Id<Surface>>>,
-}
+    pub fn get_body_by_system(&self, id: &Id<System>) -> &[Id<Body>] {
+        &self.system.body[id]
+    }
 
-impl Body {
-    pub fn insert(&mut self, id: &Id<Body>, row: BodyRow) {
-        self.system.insert(id, row.system);
-        self.name.insert(id, row.name);
-        self.mass.insert(id, row.mass);
-        self.radius.insert(id, row.radius);
-        self.position.insert(id, Default::default());
-        self.velocity.insert(id, Default::default());
-        self.orbit.insert(id, None);
-        self.surface.insert(id, None);
+    pub fn index_orbit(&mut self, id: &Id<Orbit>) {
+        if let Some(parent) = self.orbit.parent[id] {
+            self.orbit.orbit_parent[parent].push(id.id());
+        }
+    }
+
+    pub fn unindex_orbit(&mut self, id: &Id<Orbit>) {
+        if let Some(parent) = self.orbit.parent[id] {
+            self.orbit.orbit_parent[parent].retain(|r| *r != id.id());
+        }
+    }
+
+    pub fn set_orbit_parent(&mut self, id: &Id<Orbit>, value: Option<Id<Orbit>>) -> Result<(), LinkCycle> {
+        if let Some(parent) = value {
+            if parent == id.id() || self.iter_orbit_ancestors_by_parent(&parent).any(|a| a == id.id()) {
+                return Err(LinkCycle::new("Orbit", "parent"));
+            }
+        }
+
+        if let Some(parent) = self.orbit.parent[id] {
+            self.orbit.orbit_parent[parent].retain(|r| *r != id.id());
+        }
+        self.orbit.parent[id] = value;
+        if let Some(parent) = self.orbit.parent[id] {
+            self.orbit.orbit_parent[parent].push(id.id());
+        }
+        Ok(())
+    }
+
+    pub fn get_orbit_by_parent(&self, id: &Id<Orbit>) -> &[Id<Orbit>] {
+        &self.orbit.orbit_parent[id]
+    }
+
+    pub fn index_colony(&mut self, id: &Valid<Colony>) {
+        let body = self.colony.body[id];
+        self.body.colony[body].push(id.id());
+        let nation = self.colony.nation[id];
+        self.nation.colony[&Valid::new(nation)].push(id.id());
+        if let Some(supply_pod) = self.colony.supply_pod[id] {
+            self.cargo_pod.colony_supply_pod[&Valid::new(supply_pod)].push(id.id());
+        }
+    }
+
+    pub fn unindex_colony(&mut self, id: &Valid<Colony>) {
+        let body = self.colony.body[id];
+        self.body.colony[body].retain(|r| *r != id.id());
+        let nation = self.colony.nation[id];
+        self.nation.colony[&Valid::new(nation)].retain(|r| *r != id.id());
+        if let Some(supply_pod) = self.colony.supply_pod[id] {
+            self.cargo_pod.colony_supply_pod[&Valid::new(supply_pod)].retain(|r| *r != id.id());
+        }
+    }
+
+    pub fn set_colony_body(&mut self, id: &Valid<Colony>, value: Id<Body>) {
+        let body = self.colony.body[id];
+        self.body.colony[body].retain(|r| *r != id.id());
+        self.colony.body[id] = value;
+        let body = self.colony.body[id];
+        self.body.colony[body].push(id.id());
+    }
+
+    pub fn set_colony_nation(&mut self, id: &Valid<Colony>, value: GenId<Nation>) {
+        let nation = self.colony.nation[id];
+        self.nation.colony[&Valid::new(nation)].retain(|r| *r != id.id());
+        self.colony.nation[id] = value;
+        let nation = self.colony.nation[id];
+        self.nation.colony[&Valid::new(nation)].push(id.id());
+    }
+
+    pub fn set_colony_supply_pod(&mut self, id: &Valid<Colony>, value: Option<GenId<CargoPod>>) {
+        if let Some(supply_pod) = self.colony.supply_pod[id] {
+            self.cargo_pod.colony_supply_pod[&Valid::new(supply_pod)].retain(|r| *r != id.id());
+        }
+        self.colony.supply_pod[id] = value;
+        if let Some(supply_pod) = self.colony.supply_pod[id] {
+            self.cargo_pod.colony_supply_pod[&Valid::new(supply_pod)].push(id.id());
+        }
+    }
+
+    pub fn get_colony_by_body(&self, id: &Id<Body>) -> &[GenId<Colony>] {
+        &self.body.colony[id]
+    }
+
+    pub fn get_colony_by_nation(&self, id: &Valid<Nation>) -> &[GenId<Colony>] {
+        &self.nation.colony[id]
+    }
+
+    pub fn get_colony_by_supply_pod(&self, id: &Valid<CargoPod>) -> &[GenId<Colony>] {
+        &self.cargo_pod.colony_supply_pod[id]
+    }
+
+    pub fn index_vessel(&mut self, id: &Valid<Vessel>) {
+        if let Some(target) = self.vessel.target[id] {
+            self.body.vessel_target[target].push(id.id());
+        }
+        if let Some(home) = self.vessel.home[id] {
+            self.colony.vessel_home[&Valid::new(home)].push(id.id());
+        }
+    }
+
+    pub fn unindex_vessel(&mut self, id: &Valid<Vessel>) {
+        if let Some(target) = self.vessel.target[id] {
+            self.body.vessel_target[target].retain(|r| *r != id.id());
+        }
+        if let Some(home) = self.vessel.home[id] {
+            self.colony.vessel_home[&Valid::new(home)].retain(|r| *r != id.id());
+        }
+    }
+
+    pub fn update_vessel_target(&mut self, id: &Valid<Vessel>, value: Option<Id<Body>>) {
+        if let Some(target) = self.vessel.target[id] {
+            self.body.vessel_target[target].retain(|r| *r != id.id());
+        }
+        self.vessel.target[id] = value;
+        if let Some(target) = self.vessel.target[id] {
+            self.body.vessel_target[target].push(id.id());
+        }
+    }
+
+    pub fn set_vessel_target(&mut self, id: &Valid<Vessel>, value: Id<Body>) {
+        self.update_vessel_target(id, Some(value))
+    }
+
+    pub fn clear_vessel_target(&mut self, id: &Valid<Vessel>) {
+        self.update_vessel_target(id, None)
+    }
+
+    pub fn update_vessel_home(&mut self, id: &Valid<Vessel>, value: Option<GenId<Colony>>) {
+        if let Some(home) = self.vessel.home[id] {
+            self.colony.vessel_home[&Valid::new(home)].retain(|r| *r != id.id());
+        }
+        self.vessel.home[id] = value;
+        if let Some(home) = self.vessel.home[id] {
+            self.colony.vessel_home[&Valid::new(home)].push(id.id());
+        }
+    }
+
+    pub fn set_vessel_home(&mut self, id: &Valid<Vessel>, value: GenId<Colony>) {
+        self.update_vessel_home(id, Some(value))
+    }
+
+    pub fn clear_vessel_home(&mut self, id: &Valid<Vessel>) {
+        self.update_vessel_home(id, None)
+    }
+
+    pub fn get_vessel_by_target(&self, id: &Id<Body>) -> &[GenId<Vessel>] {
+        &self.body.vessel_target[id]
+    }
+
+    pub fn get_vessel_by_home(&self, id: &Valid<Colony>) -> &[GenId<Vessel>] {
+        &self.colony.vessel_home[id]
+    }
+
+    pub fn index_contract(&mut self, id: &Valid<Contract>) {
+        let pod = self.contract.pod[id];
+        self.cargo_pod.contract_pod[&Valid::new(pod)].push(id.id());
+    }
+
+    pub fn unindex_contract(&mut self, id: &Valid<Contract>) {
+        let pod = self.contract.pod[id];
+        self.cargo_pod.contract_pod[&Valid::new(pod)].retain(|r| *r != id.id());
+    }
+
+    pub fn set_contract_pod(&mut self, id: &Valid<Contract>, value: GenId<CargoPod>) {
+        let pod = self.contract.pod[id];
+        self.cargo_pod.contract_pod[&Valid::new(pod)].retain(|r| *r != id.id());
+        self.contract.pod[id] = value;
+        let pod = self.contract.pod[id];
+        self.cargo_pod.contract_pod[&Valid::new(pod)].push(id.id());
+    }
+
+    pub fn get_contract_by_pod(&self, id: &Valid<CargoPod>) -> &[GenId<Contract>] {
+        &self.cargo_pod.contract_pod[id]
+    }
+
+    pub fn index_repair_order(&mut self, id: &Valid<RepairOrder>) {
+        if let Some(engine) = self.repair_order.engine[id] {
+            self.engine.repair_order[&Valid::new(engine)].push(id.id());
+        }
+    }
+
+    pub fn unindex_repair_order(&mut self, id: &Valid<RepairOrder>) {
+        if let Some(engine) = self.repair_order.engine[id] {
+            self.engine.repair_order[&Valid::new(engine)].retain(|r| *r != id.id());
+        }
+    }
+
+    pub fn set_repair_order_engine(&mut self, id: &Valid<RepairOrder>, value: Option<GenId<Engine>>) {
+        if let Some(engine) = self.repair_order.engine[id] {
+            self.engine.repair_order[&Valid::new(engine)].retain(|r| *r != id.id());
+        }
+        self.repair_order.engine[id] = value;
+        if let Some(engine) = self.repair_order.engine[id] {
+            self.engine.repair_order[&Valid::new(engine)].push(id.id());
+        }
+    }
+
+    pub fn get_repair_order_by_engine(&self, id: &Valid<Engine>) -> &[GenId<RepairOrder>] {
+        &self.engine.repair_order[id]
+    }
+
+    pub fn index_vessel_transit(&mut self, id: &Valid<VesselTransit>) {
+        let from = self.vessel_transit.from[id];
+        self.body.vessel_transit_from[from].push(id.id());
+        let to = self.vessel_transit.to[id];
+        self.body.vessel_transit_to[to].push(id.id());
+    }
+
+    pub fn unindex_vessel_transit(&mut self, id: &Valid<VesselTransit>) {
+        let from = self.vessel_transit.from[id];
+        self.body.vessel_transit_from[from].retain(|r| *r != id.id());
+        let to = self.vessel_transit.to[id];
+        self.body.vessel_transit_to[to].retain(|r| *r != id.id());
+    }
+
+    pub fn set_vessel_transit_from(&mut self, id: &Valid<VesselTransit>, value: Id<Body>) {
+        let from = self.vessel_transit.from[id];
+        self.body.vessel_transit_from[from].retain(|r| *r != id.id());
+        self.vessel_transit.from[id] = value;
+        let from = self.vessel_transit.from[id];
+        self.body.vessel_transit_from[from].push(id.id());
+    }
+
+    pub fn set_vessel_transit_to(&mut self, id: &Valid<VesselTransit>, value: Id<Body>) {
+        let to = self.vessel_transit.to[id];
+        self.body.vessel_transit_to[to].retain(|r| *r != id.id());
+        self.vessel_transit.to[id] = value;
+        let to = self.vessel_transit.to[id];
+        self.body.vessel_transit_to[to].push(id.id());
+    }
+
+    pub fn get_vessel_transit_by_from(&self, id: &Id<Body>) -> &[GenId<VesselTransit>] {
+        &self.body.vessel_transit_from[id]
+    }
+
+    pub fn get_vessel_transit_by_to(&self, id: &Id<Body>) -> &[GenId<VesselTransit>] {
+        &self.body.vessel_transit_to[id]
+    }
+
+    pub fn index_vessel_orbit(&mut self, id: &Valid<VesselOrbit>) {
+        if let Some(parent) = self.vessel_orbit.parent[id] {
+            self.body.vessel_orbit_parent[parent].push(id.id());
+        }
+    }
+
+    pub fn unindex_vessel_orbit(&mut self, id: &Valid<VesselOrbit>) {
+        if let Some(parent) = self.vessel_orbit.parent[id] {
+            self.body.vessel_orbit_parent[parent].retain(|r| *r != id.id());
+        }
+    }
+
+    pub fn set_vessel_orbit_parent(&mut self, id: &Valid<VesselOrbit>, value: Option<Id<Body>>) {
+        if let Some(parent) = self.vessel_orbit.parent[id] {
+            self.body.vessel_orbit_parent[parent].retain(|r| *r != id.id());
+        }
+        self.vessel_orbit.parent[id] = value;
+        if let Some(parent) = self.vessel_orbit.parent[id] {
+            self.body.vessel_orbit_parent[parent].push(id.id());
+        }
+    }
+
+    pub fn get_vessel_orbit_by_parent(&self, id: &Id<Body>) -> &[GenId<VesselOrbit>] {
+        &self.body.vessel_orbit_parent[id]
+    }
+
+    pub fn iter_orbit_ancestors_by_parent(&self, id: &Id<Orbit>) -> impl Iterator<Item = Id<Orbit>> + '_ {
+        std::iter::successors(self.orbit.parent[id], move |id| self.orbit.parent[id])
+            .take(self.orbit.parent.len())
+    }
+
+    pub fn iter_orbit_hierarchy_by_parent<'a>(&'a self, alloc: &'a FixedAllocator<Orbit>) -> impl Iterator<Item = Id<Orbit>> + 'a {
+        let mut queue = alloc
+            .ids()
+            .filter(|id| self.orbit.parent[id].is_none())
+            .map(|id| id.id())
+            .collect::<std::collections::VecDeque<_>>();
+
+        std::iter::from_fn(move || {
+            let id = queue.pop_front()?;
+            queue.extend(self.orbit.orbit_parent[&id].iter().copied());
+            Some(id)
+        })
+        .take(self.orbit.parent.len())
+    }
+
+    pub fn update_body_position(&mut self, alloc: &FixedAllocator<Body>) {
+        for id in alloc.ids() {
+            let id = &id;
+            let value = get_body_position(self.body.orbit[id].map(|link| &self.orbit.position[&link]));
+            self.body.position[id] = value;
+        }
+    }
+
+    pub fn update_body(&mut self, alloc: &FixedAllocator<Body>) {
+        self.update_body_position(alloc);
+    }
+
+    pub fn update_orbit_position(&mut self, alloc: &FixedAllocator<Orbit>) {
+        let ids = self.iter_orbit_hierarchy_by_parent(alloc).collect::<Vec<_>>();
+        for id in ids {
+            let id = &id;
+            let value = get_orbit_position(&self.orbit.relative_position[id], self.orbit.parent[id].map(|link| &self.orbit.position[&link]));
+            self.orbit.position[id] = value;
+        }
+    }
+
+    pub fn update_orbit_depth(&mut self, alloc: &FixedAllocator<Orbit>) {
+        let ids = self.iter_orbit_hierarchy_by_parent(alloc).collect::<Vec<_>>();
+        for id in ids {
+            let id = &id;
+            let value = get_orbit_depth(self.orbit.parent[id].map(|link| &self.orbit.depth[&link]));
+            self.orbit.depth[id] = value;
+        }
+    }
+
+    pub fn update_orbit(&mut self, alloc: &FixedAllocator<Orbit>) {
+        self.update_orbit_position(alloc);
+        self.update_orbit_depth(alloc);
+    }
+
+    pub fn update_surface_absorption(&mut self) {
+        let surface = &mut self.surface;
+        surface.absorption
+            .iter_mut()
+            .zip(surface.albedo.iter())
+            .for_each(|(value, albedo)| *value = get_absorption(albedo));
+    }
+
+    pub fn update_surface(&mut self) {
+        self.update_surface_absorption();
+    }
+
+    pub fn link_trade_route_colonies(&mut self, id: &Valid<TradeRoute>, target: &Valid<Colony>) {
+        if !self.trade_route.colonies[id].contains(&target.id()) {
+            self.trade_route.colonies[id].push(target.id());
+            self.colony.trade_route_colonies[target].push(id.id());
+        }
+    }
+
+    pub fn unlink_trade_route_colonies(&mut self, id: &Valid<TradeRoute>, target: &Valid<Colony>) {
+        self.trade_route.colonies[id].retain(|r| *r != target.id());
+        self.colony.trade_route_colonies[target].retain(|r| *r != id.id());
+    }
+
+    pub fn iter_trade_route_colonies(&self, id: &Valid<TradeRoute>) -> impl Iterator<Item = GenId<Colony>> + '_ {
+        self.trade_route.colonies[id].iter().copied()
+    }
+
+    pub fn iter_colony_trade_route_colonies(&self, id: &Valid<Colony>) -> impl Iterator<Item = GenId<TradeRoute>> + '_ {
+        self.colony.trade_route_colonies[id].iter().copied()
+    }
+
+    pub fn prune_colony_links(&mut self, id: &Valid<Colony>) {
+        for referrer in self.colony.trade_route_colonies[id].iter() {
+            self.trade_route.colonies[&Valid::new(*referrer)].retain(|r| *r != id.id());
+        }
+    }
+
+    pub fn prune_trade_route_links(&mut self, id: &Valid<TradeRoute>) {
+        for target in self.trade_route.colonies[id].iter() {
+            self.colony.trade_route_colonies[&Valid::new(*target)].retain(|r| *r != id.id());
+        }
+    }
+
+    pub fn can_delete_vessel(&self, id: &Valid<Vessel>) -> Result<(), DeleteRestricted> {
+        if let Some(child) = self.vessel.engine[id] {
+            self.can_delete_engine(&Valid::new(child))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn can_delete_engine(&self, id: &Valid<Engine>) -> Result<(), DeleteRestricted> {
+        if !self.engine.repair_order[id].is_empty() {
+            return Err(DeleteRestricted::new("RepairOrder", "engine"));
+        }
+
+        Ok(())
+    }
+
+    pub fn get_system_row(&self, id: &Id<System>) -> SystemRow {
+        SystemRow {
+            name: self.system.name[id].clone(),
+            position: self.system.position[id].clone(),
+            temperature: self.system.temperature[id].clone(),
+            radius: self.system.radius[id].clone(),
+        }
+    }
+
+    pub fn get_body_row(&self, id: &Id<Body>) -> BodyRow {
+        BodyRow {
+            system: self.body.system[id].clone(),
+            name: self.body.name[id].clone(),
+            mass: self.body.mass[id].clone(),
+            radius: self.body.radius[id].clone(),
+        }
+    }
+
+    pub fn get_orbit_row(&self, id: &Id<Orbit>) -> OrbitRow {
+        OrbitRow {
+            parent: self.orbit.parent[id].clone(),
+            period: self.orbit.period[id].clone(),
+            radius: self.orbit.radius[id].clone(),
+        }
+    }
+
+    pub fn get_surface_row(&self, id: &Id<Surface>) -> SurfaceRow {
+        SurfaceRow {
+            area: self.surface.area[id].clone(),
+            albedo: self.surface.albedo[id].clone(),
+            atmosphere: self.surface.atmosphere.get(id).cloned(),
+        }
+    }
+
+    pub fn get_mine_site_row(&self, id: &Id<MineSite>) -> MineSiteRow {
+        MineSiteRow {
+            capacity: self.mine_site.capacity[id].clone(),
+        }
+    }
+
+    pub fn get_nation_row(&self, id: &Valid<Nation>) -> NationRow {
+        NationRow {
+            name: self.nation.name[id].clone(),
+            flag: self.nation.flag[id].clone(),
+        }
+    }
+
+    pub fn get_colony_row(&self, id: &Valid<Colony>) -> ColonyRow {
+        ColonyRow {
+            body: self.colony.body[id].clone(),
+            nation: self.colony.nation[id].clone(),
+            name: self.colony.name[id].clone(),
+            population: self.colony.population[id].clone(),
+            supply_pod: self.colony.supply_pod[id].clone(),
+        }
+    }
+
+    pub fn get_trade_route_row(&self, id: &Valid<TradeRoute>) -> TradeRouteRow {
+        TradeRouteRow {
+            distance: self.trade_route.distance[id].clone(),
+        }
+    }
+
+    pub fn get_vessel_row(&self, id: &Valid<Vessel>) -> VesselRow {
+        VesselRow {
+            name: self.vessel.name[id].clone(),
+            mass: self.vessel.mass[id].clone(),
+            speed: self.vessel.speed[id].clone(),
+        }
+    }
+
+    pub fn get_engine_row(&self, id: &Valid<Engine>) -> EngineRow {
+        EngineRow {
+            thrust: self.engine.thrust[id].clone(),
+        }
+    }
+
+    pub fn get_cargo_pod_row(&self, id: &Valid<CargoPod>) -> CargoPodRow {
+        CargoPodRow {
+            cargo: self.cargo_pod.cargo[id].clone(),
+        }
+    }
+
+    pub fn get_contract_row(&self, id: &Valid<Contract>) -> ContractRow {
+        ContractRow {
+            pod: self.contract.pod[id].clone(),
+            payment: self.contract.payment[id].clone(),
+        }
+    }
+
+    pub fn get_repair_order_row(&self, id: &Valid<RepairOrder>) -> RepairOrderRow {
+        RepairOrderRow {
+            engine: self.repair_order.engine[id].clone(),
+        }
+    }
+
+    pub fn get_vessel_transit_row(&self, id: &Valid<VesselTransit>) -> VesselTransitRow {
+        VesselTransitRow {
+            departure: self.vessel_transit.departure[id].clone(),
+            arrival: self.vessel_transit.arrival[id].clone(),
+            from: self.vessel_transit.from[id].clone(),
+            to: self.vessel_transit.to[id].clone(),
+        }
+    }
+
+    pub fn get_vessel_orbit_row(&self, id: &Valid<VesselOrbit>) -> VesselOrbitRow {
+        VesselOrbitRow {
+            parent: self.vessel_orbit.parent[id].clone(),
+            period: self.vessel_orbit.period[id].clone(),
+        }
+    }
+
+    pub fn orbit_parent_parent(&self, id: &Id<Orbit>) -> Option<Id<Orbit>> {
+        let parent = self.orbit.parent[id]?;
+        self.orbit.parent[&parent]
+    }
+
+    pub fn colony_body_system(&self, id: &Valid<Colony>) -> Id<System> {
+        let body = self.colony.body[id];
+        self.body.system[&body]
+    }
+
+    pub fn vessel_target_system(&self, id: &Valid<Vessel>) -> Option<Id<System>> {
+        let target = self.vessel.target[id]?;
+        Some(self.body.system[&target])
+    }
+
+    pub fn vessel_home_body(&self, id: &Valid<Vessel>) -> Option<Id<Body>> {
+        let home = self.vessel.home[id]?;
+        Some(self.colony.body[&Valid::new(home)])
+    }
+
+    pub fn vessel_home_nation(&self, id: &Valid<Vessel>) -> Option<GenId<Nation>> {
+        let home = self.vessel.home[id]?;
+        Some(self.colony.nation[&Valid::new(home)])
+    }
+
+    pub fn vessel_home_supply_pod(&self, id: &Valid<Vessel>) -> Option<GenId<CargoPod>> {
+        let home = self.vessel.home[id]?;
+        self.colony.supply_pod[&Valid::new(home)]
+    }
+
+    pub fn vessel_transit_from_system(&self, id: &Valid<VesselTransit>) -> Id<System> {
+        let from = self.vessel_transit.from[id];
+        self.body.system[&from]
+    }
+
+    pub fn vessel_transit_to_system(&self, id: &Valid<VesselTransit>) -> Id<System> {
+        let to = self.vessel_transit.to[id];
+        self.body.system[&to]
+    }
+
+    pub fn vessel_orbit_parent_system(&self, id: &Valid<VesselOrbit>) -> Option<Id<System>> {
+        let parent = self.vessel_orbit.parent[id]?;
+        Some(self.body.system[&parent])
+    }
+
+    pub fn iter_body_with_system<'a>(&'a self, alloc: &'a FixedAllocator<Body>) -> impl Iterator<Item = (Id<Body>, BodyRef<'a>, SystemRef<'a>)> + 'a {
+        alloc.ids().zip(self.body.iter())
+            .map(move |(id, row)| {
+                let target = self.system.get(row.system);
+                (id, row, target)
+            })
+    }
+
+    pub fn iter_orbit_with_parent<'a>(&'a self, alloc: &'a FixedAllocator<Orbit>) -> impl Iterator<Item = (Id<Orbit>, OrbitRef<'a>, Option<OrbitRef<'a>>)> + 'a {
+        alloc.ids().zip(self.orbit.iter())
+            .map(move |(id, row)| {
+                let target = row.parent.map(|id| self.orbit.get(&id));
+                (id, row, target)
+            })
+    }
+
+    pub fn iter_colony_with_body<'a>(&'a self, alloc: &'a GenAllocator<Colony>) -> impl Iterator<Item = (Valid<'a, Colony>, ColonyRef<'a>, BodyRef<'a>)> + 'a {
+        self.colony.iter_living(alloc)
+            .map(move |(id, row)| {
+                let target = self.body.get(row.body);
+                (id, row, target)
+            })
+    }
+
+    pub fn iter_colony_with_nation<'a>(&'a self, alloc: &'a GenAllocator<Colony>) -> impl Iterator<Item = (Valid<'a, Colony>, ColonyRef<'a>, NationRef<'a>)> + 'a {
+        self.colony.iter_living(alloc)
+            .map(move |(id, row)| {
+                let target = self.nation.get(&Valid::new(*row.nation));
+                (id, row, target)
+            })
+    }
+
+    pub fn iter_colony_with_supply_pod<'a>(&'a self, alloc: &'a GenAllocator<Colony>) -> impl Iterator<Item = (Valid<'a, Colony>, ColonyRef<'a>, Option<CargoPodRef<'a>>)> + 'a {
+        self.colony.iter_living(alloc)
+            .map(move |(id, row)| {
+                let target = row.supply_pod.map(|id| self.cargo_pod.get(&Valid::new(id)));
+                (id, row, target)
+            })
+    }
+
+    pub fn iter_vessel_with_target<'a>(&'a self, alloc: &'a GenAllocator<Vessel>) -> impl Iterator<Item = (Valid<'a, Vessel>, VesselRef<'a>, Option<BodyRef<'a>>)> + 'a {
+        self.vessel.iter_living(alloc)
+            .map(move |(id, row)| {
+                let target = row.target.map(|id| self.body.get(&id));
+                (id, row, target)
+            })
+    }
+
+    pub fn iter_vessel_with_home<'a>(&'a self, alloc: &'a GenAllocator<Vessel>) -> impl Iterator<Item = (Valid<'a, Vessel>, VesselRef<'a>, Option<ColonyRef<'a>>)> + 'a {
+        self.vessel.iter_living(alloc)
+            .map(move |(id, row)| {
+                let target = row.home.map(|id| self.colony.get(&Valid::new(id)));
+                (id, row, target)
+            })
+    }
+
+    pub fn iter_contract_with_pod<'a>(&'a self, alloc: &'a GenAllocator<Contract>) -> impl Iterator<Item = (Valid<'a, Contract>, ContractRef<'a>, CargoPodRef<'a>)> + 'a {
+        self.contract.iter_living(alloc)
+            .map(move |(id, row)| {
+                let target = self.cargo_pod.get(&Valid::new(*row.pod));
+                (id, row, target)
+            })
+    }
+
+    pub fn iter_repair_order_with_engine<'a>(&'a self, alloc: &'a GenAllocator<RepairOrder>) -> impl Iterator<Item = (Valid<'a, RepairOrder>, RepairOrderRef<'a>, Option<EngineRef<'a>>)> + 'a {
+        self.repair_order.iter_living(alloc)
+            .map(move |(id, row)| {
+                let target = row.engine.map(|id| self.engine.get(&Valid::new(id)));
+                (id, row, target)
+            })
+    }
+
+    pub fn iter_vessel_transit_with_from<'a>(&'a self, alloc: &'a GenAllocator<VesselTransit>) -> impl Iterator<Item = (Valid<'a, VesselTransit>, VesselTransitRef<'a>, BodyRef<'a>)> + 'a {
+        self.vessel_transit.iter_living(alloc)
+            .map(move |(id, row)| {
+                let target = self.body.get(row.from);
+                (id, row, target)
+            })
+    }
+
+    pub fn iter_vessel_transit_with_to<'a>(&'a self, alloc: &'a GenAllocator<VesselTransit>) -> impl Iterator<Item = (Valid<'a, VesselTransit>, VesselTransitRef<'a>, BodyRef<'a>)> + 'a {
+        self.vessel_transit.iter_living(alloc)
+            .map(move |(id, row)| {
+                let target = self.body.get(row.to);
+                (id, row, target)
+            })
+    }
+
+    pub fn iter_vessel_orbit_with_parent<'a>(&'a self, alloc: &'a GenAllocator<VesselOrbit>) -> impl Iterator<Item = (Valid<'a, VesselOrbit>, VesselOrbitRef<'a>, Option<BodyRef<'a>>)> + 'a {
+        self.vessel_orbit.iter_living(alloc)
+            .map(move |(id, row)| {
+                let target = row.parent.map(|id| self.body.get(&id));
+                (id, row, target)
+            })
+    }
+}
+
+
+#[derive(Debug, Default, Clone)]
+pub struct System {
+    pub name: Component<Self, Option<String>>,
+    pub position: Component<Self, Position>,
+    pub temperature: Component<Self, Temperature>,
+    pub radius: Component<Self, Length>,
+    pub body: Component<Self, Vec<Id<Body>>>,
+}
+
+impl System {
+    pub fn insert(&mut self, id: &Id<System>, row: SystemRow) {
+        self.name.insert(id, row.name);
+        self.position.insert(id, row.position);
+        self.temperature.insert(id, row.temperature);
+        self.radius.insert(id, row.radius);
+        self.body.insert(id, Vec::new());
+    }
+
+    pub fn create<'a>(&mut self, row: SystemRow, alloc: &'a mut FixedAllocator<System>) -> Id<System> {
+        let id = alloc.create();
+        self.insert(&id, row);
+        id
+    }
+
+    pub fn get(&self, id: &Id<System>) -> SystemRef {
+        SystemRef {
+            name: &self.name[id],
+            position: &self.position[id],
+            temperature: &self.temperature[id],
+            radius: &self.radius[id],
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = SystemRef> + '_ {
+        self.name
+            .iter()
+            .zip(self.position.iter())
+            .zip(self.temperature.iter())
+            .zip(self.radius.iter())
+            .map(|(((name, position), temperature), radius)| SystemRef { name, position, temperature, radius })
+    }
+
+    pub fn get_mut(&mut self, id: &Id<System>) -> SystemMut {
+        SystemMut {
+            name: &mut self.name[id],
+            position: &mut self.position[id],
+            temperature: &mut self.temperature[id],
+            radius: &mut self.radius[id],
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = SystemMut> + '_ {
+        self.name
+            .iter_mut()
+            .zip(self.position.iter_mut())
+            .zip(self.temperature.iter_mut())
+            .zip(self.radius.iter_mut())
+            .map(|(((name, position), temperature), radius)| SystemMut { name, position, temperature, radius })
+    }
+}
+
+
+#[derive(Debug, Default, Clone)]
+pub struct Body {
+    pub system: Component<Self, Id<System>>,
+    pub name: Component<Self, Option<String>>,
+    pub mass: Component<Self, Mass>,
+    pub radius: Component<Self, Length>,
+    pub position: Component<Self, Position>,
+    pub velocity: Component<Self, Velocity>,
+    pub orbit: Component<Self, Option<Id<Orbit>>>,
+    pub surface: Component<Self, Option<Id<Surface>>>,
+    pub mine_site: Component<Self, Vec<Id<MineSite>>>,
+    pub colony: Component<Self, Vec<GenId<Colony>>>,
+    pub vessel_target: Component<Self, Vec<GenId<Vessel>>>,
+    pub vessel_transit_from: Component<Self, Vec<GenId<VesselTransit>>>,
+    pub vessel_transit_to: Component<Self, Vec<GenId<VesselTransit>>>,
+    pub vessel_orbit_parent: Component<Self, Vec<GenId<VesselOrbit>>>,
+}
+
+impl Body {
+    pub fn insert(&mut self, id: &Id<Body>, row: BodyRow) {
+        self.system.insert(id, row.system);
+        self.name.insert(id, row.name);
+        self.mass.insert(id, row.mass);
+        self.radius.insert(id, row.radius);
+        self.velocity.insert(id, Default::default());
+        self.position.insert(id, Default::default());
+        self.orbit.insert(id, None);
+        self.surface.insert(id, None);
+        self.mine_site.insert(id, Vec::new());
+        self.colony.insert(id, Vec::new());
+        self.vessel_target.insert(id, Vec::new());
+        self.vessel_transit_from.insert(id, Vec::new());
+        self.vessel_transit_to.insert(id, Vec::new());
+        self.vessel_orbit_parent.insert(id, Vec::new());
+    }
+
+    pub fn create<'a>(&mut self, row: BodyRow, alloc: &'a mut FixedAllocator<Body>) -> Id<Body> {
+        let id = alloc.create();
+        self.insert(&id, row);
+        id
+    }
+
+    pub fn get(&self, id: &Id<Body>) -> BodyRef {
+        BodyRef {
+            system: &self.system[id],
+            name: &self.name[id],
+            mass: &self.mass[id],
+            radius: &self.radius[id],
+            position: &self.position[id],
+            velocity: &self.velocity[id],
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = BodyRef> + '_ {
+        self.system
+            .iter()
+            .zip(self.name.iter())
+            .zip(self.mass.iter())
+            .zip(self.radius.iter())
+            .zip(self.position.iter())
+            .zip(self.velocity.iter())
+            .map(|(((((system, name), mass), radius), position), velocity)| BodyRef { system, name, mass, radius, position, velocity })
+    }
+
+    pub fn get_mut(&mut self, id: &Id<Body>) -> BodyMut {
+        BodyMut {
+            system: &mut self.system[id],
+            name: &mut self.name[id],
+            mass: &mut self.mass[id],
+            radius: &mut self.radius[id],
+            velocity: &mut self.velocity[id],
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = BodyMut> + '_ {
+        self.system
+            .iter_mut()
+            .zip(self.name.iter_mut())
+            .zip(self.mass.iter_mut())
+            .zip(self.radius.iter_mut())
+            .zip(self.velocity.iter_mut())
+            .map(|((((system, name), mass), radius), velocity)| BodyMut { system, name, mass, radius, velocity })
+    }
+}
+
+
+#[derive(Debug, Default, Clone)]
+pub struct Orbit {
+    pub body: Component<Self, Id<Body>>,
+    pub parent: Component<Self, Option<Id<Orbit>>>,
+    pub period: Component<Self, Time>,
+    pub radius: Component<Self, Length>,
+    pub relative_position: Component<Self, Position>,
+    pub position: Component<Self, Position>,
+    pub depth: Component<Self, u32>,
+    pub orbit_parent: Component<Self, Vec<Id<Orbit>>>,
+}
+
+impl Orbit {
+    pub fn insert(&mut self, id: &Id<Orbit>, row: OrbitRow) {
+        self.parent.insert(id, row.parent);
+        self.period.insert(id, row.period);
+        self.radius.insert(id, row.radius);
+        self.relative_position.insert(id, Default::default());
+        self.position.insert(id, Default::default());
+        self.depth.insert(id, Default::default());
+        self.orbit_parent.insert(id, Vec::new());
+    }
+
+    pub fn create<'a>(&mut self, row: OrbitRow, alloc: &'a mut FixedAllocator<Orbit>) -> Id<Orbit> {
+        let id = alloc.create();
+        self.insert(&id, row);
+        id
+    }
+
+    pub fn get(&self, id: &Id<Orbit>) -> OrbitRef {
+        OrbitRef {
+            parent: &self.parent[id],
+            period: &self.period[id],
+            radius: &self.radius[id],
+            relative_position: &self.relative_position[id],
+            position: &self.position[id],
+            depth: &self.depth[id],
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = OrbitRef> + '_ {
+        self.parent
+            .iter()
+            .zip(self.period.iter())
+            .zip(self.radius.iter())
+            .zip(self.relative_position.iter())
+            .zip(self.position.iter())
+            .zip(self.depth.iter())
+            .map(|(((((parent, period), radius), relative_position), position), depth)| OrbitRef { parent, period, radius, relative_position, position, depth })
+    }
+
+    pub fn get_mut(&mut self, id: &Id<Orbit>) -> OrbitMut {
+        OrbitMut {
+            parent: &mut self.parent[id],
+            period: &mut self.period[id],
+            radius: &mut self.radius[id],
+            relative_position: &mut self.relative_position[id],
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = OrbitMut> + '_ {
+        self.parent
+            .iter_mut()
+            .zip(self.period.iter_mut())
+            .zip(self.radius.iter_mut())
+            .zip(self.relative_position.iter_mut())
+            .map(|(((parent, period), radius), relative_position)| OrbitMut { parent, period, radius, relative_position })
+    }
+}
+
+
+#[derive(Debug, Default, Clone)]
+pub struct Surface {
+    pub body: Component<Self, Id<Body>>,
+    pub area: Component<Self, Area>,
+    pub albedo: Component<Self, Albedo>,
+    pub temperature: Component<Self, Temperature>,
+    pub atmosphere: ComponentMap<Id<Surface>, Atmosphere>,
+    pub absorption: Component<Self, Absorption>,
+}
+
+impl Surface {
+    pub fn insert(&mut self, id: &Id<Surface>, row: SurfaceRow) {
+        self.temperature.insert(id, Temperature::in_kelvin(3.0));
+        self.area.insert(id, row.area);
+        self.albedo.insert(id, row.albedo);
+        if let Some(atmosphere) = row.atmosphere {
+            self.atmosphere.insert(id, atmosphere);
+        }
+        self.absorption.insert(id, get_absorption(&self.albedo[id]));
+    }
+
+    pub fn create<'a>(&mut self, row: SurfaceRow, alloc: &'a mut FixedAllocator<Surface>) -> Id<Surface> {
+        let id = alloc.create();
+        self.insert(&id, row);
+        id
+    }
+
+    pub fn get(&self, id: &Id<Surface>) -> SurfaceRef {
+        SurfaceRef {
+            area: &self.area[id],
+            albedo: &self.albedo[id],
+            temperature: &self.temperature[id],
+            absorption: &self.absorption[id],
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = SurfaceRef> + '_ {
+        self.area
+            .iter()
+            .zip(self.albedo.iter())
+            .zip(self.temperature.iter())
+            .zip(self.absorption.iter())
+            .map(|(((area, albedo), temperature), absorption)| SurfaceRef { area, albedo, temperature, absorption })
+    }
+
+    pub fn get_mut(&mut self, id: &Id<Surface>) -> SurfaceMut {
+        SurfaceMut {
+            area: &mut self.area[id],
+            albedo: &mut self.albedo[id],
+            temperature: &mut self.temperature[id],
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = SurfaceMut> + '_ {
+        self.area
+            .iter_mut()
+            .zip(self.albedo.iter_mut())
+            .zip(self.temperature.iter_mut())
+            .map(|((area, albedo), temperature)| SurfaceMut { area, albedo, temperature })
+    }
+}
+
+
+#[derive(Debug, Default, Clone)]
+pub struct MineSite {
+    pub body: Component<Self, Id<Body>>,
+    pub capacity: Component<Self, Mass>,
+}
+
+impl MineSite {
+    pub fn insert(&mut self, id: &Id<MineSite>, row: MineSiteRow) {
+        self.capacity.insert(id, row.capacity);
+    }
+
+    pub fn create<'a>(&mut self, row: MineSiteRow, alloc: &'a mut FixedAllocator<MineSite>) -> Id<MineSite> {
+        let id = alloc.create();
+        self.insert(&id, row);
+        id
+    }
+
+    pub fn get(&self, id: &Id<MineSite>) -> MineSiteRef {
+        MineSiteRef {
+            capacity: &self.capacity[id],
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = MineSiteRef> + '_ {
+        self.capacity
+            .iter()
+            .map(|capacity| MineSiteRef { capacity })
+    }
+
+    pub fn get_mut(&mut self, id: &Id<MineSite>) -> MineSiteMut {
+        MineSiteMut {
+            capacity: &mut self.capacity[id],
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = MineSiteMut> + '_ {
+        self.capacity
+            .iter_mut()
+            .map(|capacity| MineSiteMut { capacity })
+    }
+}
+
+
+#[derive(Debug, Default, Clone)]
+pub struct Nation {
+    pub name: Component<Self, String>,
+    pub flag: Component<Self, Flag>,
+    pub population: Component<Self, Population>,
+    pub colony: Component<Self, Vec<GenId<Colony>>>,
+}
+
+impl Nation {
+    pub fn insert(&mut self, id: &Valid<Nation>, row: NationRow) {
+        self.name.insert(id, row.name);
+        self.flag.insert(id, row.flag);
+        self.population.insert(id, Default::default());
+        self.colony.insert(id, Vec::new());
+    }
+
+    pub fn create<'a>(&mut self, row: NationRow, alloc: &'a mut GenAllocator<Nation>) -> Valid<'a, Nation> {
+        let id = alloc.create();
+        self.insert(&id, row);
+        id
+    }
+
+    pub fn remove(&mut self, id: &Valid<Nation>) {
+        self.name.clear(id);
+        self.population.clear(id);
+        self.colony.clear(id);
+    }
+
+    pub fn get(&self, id: &Valid<Nation>) -> NationRef {
+        NationRef {
+            name: &self.name[id],
+            flag: &self.flag[id],
+            population: &self.population[id],
+        }
+    }
+
+    pub fn iter_living<'a>(&'a self, alloc: &'a GenAllocator<Nation>) -> impl Iterator<Item = (Valid<'a, Nation>, NationRef<'a>)> + 'a {
+        self.name
+            .iter()
+            .zip(self.flag.iter())
+            .zip(self.population.iter())
+            .map(|((name, flag), population)| NationRef { name, flag, population })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
+
+    pub fn get_mut(&mut self, id: &Valid<Nation>) -> NationMut {
+        NationMut {
+            name: &mut self.name[id],
+            flag: &mut self.flag[id],
+            population: &mut self.population[id],
+        }
+    }
+
+    pub fn iter_living_mut<'a>(&'a mut self, alloc: &'a GenAllocator<Nation>) -> impl Iterator<Item = (Valid<'a, Nation>, NationMut<'a>)> + 'a {
+        self.name
+            .iter_mut()
+            .zip(self.flag.iter_mut())
+            .zip(self.population.iter_mut())
+            .map(|((name, flag), population)| NationMut { name, flag, population })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
+}
+
+
+#[derive(Debug, Default, Clone)]
+pub struct Colony {
+    pub body: Component<Self, Id<Body>>,
+    pub nation: Component<Self, GenId<Nation>>,
+    pub name: Component<Self, String>,
+    pub population: Component<Self, Population>,
+    pub supply_pod: Component<Self, Option<GenId<CargoPod>>>,
+    pub is_capital: TagComponent<GenId<Colony>>,
+    pub vessel_home: Component<Self, Vec<GenId<Vessel>>>,
+    pub trade_route_colonies: Component<Self, Vec<GenId<TradeRoute>>>,
+}
+
+impl Colony {
+    pub fn insert(&mut self, id: &Valid<Colony>, row: ColonyRow) {
+        self.body.insert(id, row.body);
+        self.nation.insert(id, row.nation);
+        self.name.insert(id, row.name);
+        self.population.insert(id, row.population);
+        self.supply_pod.insert(id, row.supply_pod);
+        self.is_capital.unset(id);
+        self.vessel_home.insert(id, Vec::new());
+        self.trade_route_colonies.insert(id, Vec::new());
+    }
+
+    pub fn create<'a>(&mut self, row: ColonyRow, alloc: &'a mut GenAllocator<Colony>) -> Valid<'a, Colony> {
+        let id = alloc.create();
+        self.insert(&id, row);
+        id
+    }
+
+    pub fn remove(&mut self, id: &Valid<Colony>) {
+        self.name.clear(id);
+        self.supply_pod.remove(id);
+        self.is_capital.unset(id);
+        self.vessel_home.clear(id);
+        self.trade_route_colonies.clear(id);
+    }
+
+    pub fn get(&self, id: &Valid<Colony>) -> ColonyRef {
+        ColonyRef {
+            body: &self.body[id],
+            nation: &self.nation[id],
+            name: &self.name[id],
+            population: &self.population[id],
+            supply_pod: &self.supply_pod[id],
+        }
+    }
+
+    pub fn iter_living<'a>(&'a self, alloc: &'a GenAllocator<Colony>) -> impl Iterator<Item = (Valid<'a, Colony>, ColonyRef<'a>)> + 'a {
+        self.body
+            .iter()
+            .zip(self.nation.iter())
+            .zip(self.name.iter())
+            .zip(self.population.iter())
+            .zip(self.supply_pod.iter())
+            .map(|((((body, nation), name), population), supply_pod)| ColonyRef { body, nation, name, population, supply_pod })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
+
+    pub fn get_mut(&mut self, id: &Valid<Colony>) -> ColonyMut {
+        ColonyMut {
+            body: &mut self.body[id],
+            nation: &mut self.nation[id],
+            name: &mut self.name[id],
+            population: &mut self.population[id],
+            supply_pod: &mut self.supply_pod[id],
+        }
+    }
+
+    pub fn iter_living_mut<'a>(&'a mut self, alloc: &'a GenAllocator<Colony>) -> impl Iterator<Item = (Valid<'a, Colony>, ColonyMut<'a>)> + 'a {
+        self.body
+            .iter_mut()
+            .zip(self.nation.iter_mut())
+            .zip(self.name.iter_mut())
+            .zip(self.population.iter_mut())
+            .zip(self.supply_pod.iter_mut())
+            .map(|((((body, nation), name), population), supply_pod)| ColonyMut { body, nation, name, population, supply_pod })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
+}
+
+
+#[derive(Debug, Default, Clone)]
+pub struct TradeRoute {
+    pub distance: Component<Self, Length>,
+    pub colonies: Component<Self, Vec<GenId<Colony>>>,
+}
+
+impl TradeRoute {
+    pub fn insert(&mut self, id: &Valid<TradeRoute>, row: TradeRouteRow) {
+        self.distance.insert(id, row.distance);
+        self.colonies.insert(id, Vec::new());
+    }
+
+    pub fn create<'a>(&mut self, row: TradeRouteRow, alloc: &'a mut GenAllocator<TradeRoute>) -> Valid<'a, TradeRoute> {
+        let id = alloc.create();
+        self.insert(&id, row);
+        id
+    }
+
+    pub fn remove(&mut self, id: &Valid<TradeRoute>) {
+        self.colonies.clear(id);
+    }
+
+    pub fn get(&self, id: &Valid<TradeRoute>) -> TradeRouteRef {
+        TradeRouteRef {
+            distance: &self.distance[id],
+        }
+    }
+
+    pub fn iter_living<'a>(&'a self, alloc: &'a GenAllocator<TradeRoute>) -> impl Iterator<Item = (Valid<'a, TradeRoute>, TradeRouteRef<'a>)> + 'a {
+        self.distance
+            .iter()
+            .map(|distance| TradeRouteRef { distance })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
+
+    pub fn get_mut(&mut self, id: &Valid<TradeRoute>) -> TradeRouteMut {
+        TradeRouteMut {
+            distance: &mut self.distance[id],
+        }
+    }
+
+    pub fn iter_living_mut<'a>(&'a mut self, alloc: &'a GenAllocator<TradeRoute>) -> impl Iterator<Item = (Valid<'a, TradeRoute>, TradeRouteMut<'a>)> + 'a {
+        self.distance
+            .iter_mut()
+            .map(|distance| TradeRouteMut { distance })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
+}
+
+
+#[derive(Debug, Default, Clone)]
+pub struct Vessel {
+    pub name: Component<Self, String>,
+    pub mass: Component<Self, Mass>,
+    pub speed: Component<Self, Speed>,
+    pub registry: Component<Self, Registry>,
+    pub target: Component<Self, Option<Id<Body>>>,
+    pub home: Component<Self, Option<GenId<Colony>>>,
+    pub vessel_location: Component<Self, VesselLocation>,
+    pub engine: Component<Self, Option<GenId<Engine>>>,
+    pub cargo_pod: Component<Self, Vec<GenId<CargoPod>>>,
+}
+
+impl Vessel {
+    pub fn insert(&mut self, id: &Valid<Vessel>, row: VesselRow) {
+        self.registry.insert(id, ((|row| Registry(row.name.to_uppercase())) as fn(&VesselRow) -> Registry)(&row));
+        self.name.insert(id, row.name);
+        self.mass.insert(id, row.mass);
+        self.speed.insert(id, row.speed);
+        self.target.insert(id, Default::default());
+        self.home.insert(id, Default::default());
+        self.engine.insert(id, None);
+        self.cargo_pod.insert(id, Vec::new());
+    }
+
+    pub fn create<'a>(&mut self, row: VesselRow, alloc: &'a mut GenAllocator<Vessel>) -> Valid<'a, Vessel> {
+        let id = alloc.create();
+        self.insert(&id, row);
+        id
+    }
+
+    pub fn remove(&mut self, id: &Valid<Vessel>) {
+        self.name.clear(id);
+        self.registry.clear(id);
+        self.target.remove(id);
+        self.home.remove(id);
+        self.engine.remove(id);
+        self.cargo_pod.clear(id);
+    }
+
+    pub fn get(&self, id: &Valid<Vessel>) -> VesselRef {
+        VesselRef {
+            name: &self.name[id],
+            mass: &self.mass[id],
+            speed: &self.speed[id],
+            registry: &self.registry[id],
+            target: &self.target[id],
+            home: &self.home[id],
+        }
+    }
+
+    pub fn iter_living<'a>(&'a self, alloc: &'a GenAllocator<Vessel>) -> impl Iterator<Item = (Valid<'a, Vessel>, VesselRef<'a>)> + 'a {
+        self.name
+            .iter()
+            .zip(self.mass.iter())
+            .zip(self.speed.iter())
+            .zip(self.registry.iter())
+            .zip(self.target.iter())
+            .zip(self.home.iter())
+            .map(|(((((name, mass), speed), registry), target), home)| VesselRef { name, mass, speed, registry, target, home })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
+
+    pub fn get_mut(&mut self, id: &Valid<Vessel>) -> VesselMut {
+        VesselMut {
+            name: &mut self.name[id],
+            mass: &mut self.mass[id],
+            speed: &mut self.speed[id],
+            registry: &mut self.registry[id],
+            target: &mut self.target[id],
+            home: &mut self.home[id],
+        }
+    }
+
+    pub fn iter_living_mut<'a>(&'a mut self, alloc: &'a GenAllocator<Vessel>) -> impl Iterator<Item = (Valid<'a, Vessel>, VesselMut<'a>)> + 'a {
+        self.name
+            .iter_mut()
+            .zip(self.mass.iter_mut())
+            .zip(self.speed.iter_mut())
+            .zip(self.registry.iter_mut())
+            .zip(self.target.iter_mut())
+            .zip(self.home.iter_mut())
+            .map(|(((((name, mass), speed), registry), target), home)| VesselMut { name, mass, speed, registry, target, home })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
+}
+
+
+#[derive(Debug, Default, Clone)]
+pub struct Engine {
+    pub vessel: Component<Self, GenId<Vessel>>,
+    pub thrust: Component<Self, Force>,
+    pub repair_order: Component<Self, Vec<GenId<RepairOrder>>>,
+}
+
+impl Engine {
+    pub fn insert(&mut self, id: &Valid<Engine>, row: EngineRow) {
+        self.thrust.insert(id, row.thrust);
+        self.repair_order.insert(id, Vec::new());
+    }
+
+    pub fn create<'a>(&mut self, row: EngineRow, alloc: &'a mut GenAllocator<Engine>) -> Valid<'a, Engine> {
+        let id = alloc.create();
+        self.insert(&id, row);
+        id
+    }
+
+    pub fn remove(&mut self, id: &Valid<Engine>) {
+        self.repair_order.clear(id);
+    }
+
+    pub fn get(&self, id: &Valid<Engine>) -> EngineRef {
+        EngineRef {
+            thrust: &self.thrust[id],
+        }
+    }
+
+    pub fn iter_living<'a>(&'a self, alloc: &'a GenAllocator<Engine>) -> impl Iterator<Item = (Valid<'a, Engine>, EngineRef<'a>)> + 'a {
+        self.thrust
+            .iter()
+            .map(|thrust| EngineRef { thrust })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
+
+    pub fn get_mut(&mut self, id: &Valid<Engine>) -> EngineMut {
+        EngineMut {
+            thrust: &mut self.thrust[id],
+        }
     }
 
-    pub fn create<'a>(&mut self, row: BodyRow, alloc: &'a mut FixedAllocator<Body>) -> Id<Body> {
-        let id = alloc.create();
-        self.insert(&id, row);
-        id
+    pub fn iter_living_mut<'a>(&'a mut self, alloc: &'a GenAllocator<Engine>) -> impl Iterator<Item = (Valid<'a, Engine>, EngineMut<'a>)> + 'a {
+        self.thrust
+            .iter_mut()
+            .map(|thrust| EngineMut { thrust })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
     }
 }
 
 
 #[derive(Debug, Default, Clone)]
-pub struct Orbit {
-    pub body: Component<Self, Id<Body>>,
-    pub parent: Component<Self, Option<Id<Orbit>>>,
-    pub period: Component<Self, Time>,
-    pub radius: Component<Self, Length>,
-    pub relative_position: Component<Self, Position>,
+pub struct CargoPod {
+    pub vessel: Component<Self, GenId<Vessel>>,
+    pub cargo: Component<Self, Mass>,
+    pub colony_supply_pod: Component<Self, Vec<GenId<Colony>>>,
+    pub contract_pod: Component<Self, Vec<GenId<Contract>>>,
 }
 
-impl Orbit {
-    pub fn insert(&mut self, id: &Id<Orbit>, row: OrbitRow) {
-        self.parent.insert(id, row.parent);
-        self.period.insert(id, row.period);
-        self.radius.insert(id, row.radius);
-        self.relative_position.insert(id, Default::default());
+impl CargoPod {
+    pub fn insert(&mut self, id: &Valid<CargoPod>, row: CargoPodRow) {
+        self.cargo.insert(id, row.cargo);
+        self.colony_supply_pod.insert(id, Vec::new());
+        self.contract_pod.insert(id, Vec::new());
     }
 
-    pub fn create<'a>(&mut self, row: OrbitRow, alloc: &'a mut FixedAllocator<Orbit>) -> Id<Orbit> {
+    pub fn create<'a>(&mut self, row: CargoPodRow, alloc: &'a mut GenAllocator<CargoPod>) -> Valid<'a, CargoPod> {
         let id = alloc.create();
         self.insert(&id, row);
         id
     }
-}
 
+    pub fn remove(&mut self, id: &Valid<CargoPod>) {
+        self.colony_supply_pod.clear(id);
+        self.contract_pod.clear(id);
+    }
 
-#[derive(Debug, Default, Clone)]
-pub struct Surface {
-    pub body: Component<Self, Id<Body>>,
-    pub area: Component<Self, Area>,
-    pub albedo: Component<Self, Albedo>,
-    pub temperature: Component<Self, Temperature>,
-}
+    pub fn get(&self, id: &Valid<CargoPod>) -> CargoPodRef {
+        CargoPodRef {
+            cargo: &self.cargo[id],
+        }
+    }
 
-impl Surface {
-    pub fn insert(&mut self, id: &Id<Surface>, row: SurfaceRow) {
-        self.area.insert(id, row.area);
-        self.albedo.insert(id, row.albedo);
-        self.temperature.insert(id, Default::default());
+    pub fn iter_living<'a>(&'a self, alloc: &'a GenAllocator<CargoPod>) -> impl Iterator<Item = (Valid<'a, CargoPod>, CargoPodRef<'a>)> + 'a {
+        self.cargo
+            .iter()
+            .map(|cargo| CargoPodRef { cargo })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
     }
 
-    pub fn create<'a>(&mut self, row: SurfaceRow, alloc: &'a mut FixedAllocator<Surface>) -> Id<Surface> {
-        let id = alloc.create();
-        self.insert(&id, row);
-        id
+    pub fn get_mut(&mut self, id: &Valid<CargoPod>) -> CargoPodMut {
+        CargoPodMut {
+            cargo: &mut self.cargo[id],
+        }
+    }
+
+    pub fn iter_living_mut<'a>(&'a mut self, alloc: &'a GenAllocator<CargoPod>) -> impl Iterator<Item = (Valid<'a, CargoPod>, CargoPodMut<'a>)> + 'a {
+        self.cargo
+            .iter_mut()
+            .map(|cargo| CargoPodMut { cargo })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
     }
 }
 
 
 #[derive(Debug, Default, Clone)]
-pub struct Nation {
-    pub name: Component<Self, String>,
-    pub population: Component<Self, Population>,
+pub struct Contract {
+    pub pod: Component<Self, GenId<CargoPod>>,
+    pub payment: Component<Self, f64>,
 }
 
-impl Nation {
-    pub fn insert(&mut self, id: &Valid<Nation>, row: NationRow) {
-        self.name.insert(id, row.name);
-        self.population.insert(id, Default::default());
+impl Contract {
+    pub fn insert(&mut self, id: &Valid<Contract>, row: ContractRow) {
+        self.pod.insert(id, row.pod);
+        self.payment.insert(id, row.payment);
     }
 
-    pub fn create<'a>(&mut self, row: NationRow, alloc: &'a mut GenAllocator<Nation>) -> Valid<'a, Nation> {
+    pub fn create<'a>(&mut self, row: ContractRow, alloc: &'a mut GenAllocator<Contract>) -> Valid<'a, Contract> {
         let id = alloc.create();
         self.insert(&id, row);
         id
     }
-}
 
+    pub fn remove(&mut self, _id: &Valid<Contract>) {
+    }
 
-#[derive(Debug, Default, Clone)]
-pub struct Colony {
-    pub body: Component<Self, Id<Body>>,
-    pub nation: Component<Self, GenId<Nation>>,
-    pub name: Component<Self, String>,
-    pub population: Component<Self, Population>,
-}
+    pub fn get(&self, id: &Valid<Contract>) -> ContractRef {
+        ContractRef {
+            pod: &self.pod[id],
+            payment: &self.payment[id],
+        }
+    }
 
-impl Colony {
-    pub fn insert(&mut self, id: &Valid<Colony>, row: ColonyRow) {
-        self.body.insert(id, row.body);
-        self.nation.insert(id, row.nation);
-        self.name.insert(id, row.name);
-        self.population.insert(id, row.population);
+    pub fn iter_living<'a>(&'a self, alloc: &'a GenAllocator<Contract>) -> impl Iterator<Item = (Valid<'a, Contract>, ContractRef<'a>)> + 'a {
+        self.pod
+            .iter()
+            .zip(self.payment.iter())
+            .map(|(pod, payment)| ContractRef { pod, payment })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
     }
 
-    pub fn create<'a>(&mut self, row: ColonyRow, alloc: &'a mut GenAllocator<Colony>) -> Valid<'a, Colony> {
-        let id = alloc.create();
-        self.insert(&id, row);
-        id
+    pub fn get_mut(&mut self, id: &Valid<Contract>) -> ContractMut {
+        ContractMut {
+            pod: &mut self.pod[id],
+            payment: &mut self.payment[id],
+        }
+    }
+
+    pub fn iter_living_mut<'a>(&'a mut self, alloc: &'a GenAllocator<Contract>) -> impl Iterator<Item = (Valid<'a, Contract>, ContractMut<'a>)> + 'a {
+        self.pod
+            .iter_mut()
+            .zip(self.payment.iter_mut())
+            .map(|(pod, payment)| ContractMut { pod, payment })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
     }
 }
 
 
 #[derive(Debug, Default, Clone)]
-pub struct Vessel {
-    pub name: Component<Self, String>,
-    pub mass: Component<Self, Mass>,
-    pub speed: Component<Self, Speed>,
-    pub vessel_location: Component<Self, VesselLocation>,
+pub struct RepairOrder {
     pub engine: Component<Self, Option<GenId<Engine>>>,
 }
 
-impl Vessel {
-    pub fn insert(&mut self, id: &Valid<Vessel>, row: VesselRow) {
-        self.name.insert(id, row.name);
-        self.mass.insert(id, row.mass);
-        self.speed.insert(id, row.speed);
-        self.engine.insert(id, None);
+impl RepairOrder {
+    pub fn insert(&mut self, id: &Valid<RepairOrder>, row: RepairOrderRow) {
+        self.engine.insert(id, row.engine);
     }
 
-    pub fn create<'a>(&mut self, row: VesselRow, alloc: &'a mut GenAllocator<Vessel>) -> Valid<'a, Vessel> {
+    pub fn create<'a>(&mut self, row: RepairOrderRow, alloc: &'a mut GenAllocator<RepairOrder>) -> Valid<'a, RepairOrder> {
         let id = alloc.create();
         self.insert(&id, row);
         id
     }
-}
 
+    pub fn remove(&mut self, id: &Valid<RepairOrder>) {
+        self.engine.remove(id);
+    }
 
-#[derive(Debug, Default, Clone)]
-pub struct Engine {
-    pub vessel: Component<Self, GenId<Vessel>>,
-    pub thrust: Component<Self, Force>,
-}
+    pub fn get(&self, id: &Valid<RepairOrder>) -> RepairOrderRef {
+        RepairOrderRef {
+            engine: &self.engine[id],
+        }
+    }
 
-impl Engine {
-    pub fn insert(&mut self, id: &Valid<Engine>, row: EngineRow) {
-        self.thrust.insert(id, row.thrust);
+    pub fn iter_living<'a>(&'a self, alloc: &'a GenAllocator<RepairOrder>) -> impl Iterator<Item = (Valid<'a, RepairOrder>, RepairOrderRef<'a>)> + 'a {
+        self.engine
+            .iter()
+            .map(|engine| RepairOrderRef { engine })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
     }
 
-    pub fn create<'a>(&mut self, row: EngineRow, alloc: &'a mut GenAllocator<Engine>) -> Valid<'a, Engine> {
-        let id = alloc.create();
-        self.insert(&id, row);
-        id
+    pub fn get_mut(&mut self, id: &Valid<RepairOrder>) -> RepairOrderMut {
+        RepairOrderMut {
+            engine: &mut self.engine[id],
+        }
+    }
+
+    pub fn iter_living_mut<'a>(&'a mut self, alloc: &'a GenAllocator<RepairOrder>) -> impl Iterator<Item = (Valid<'a, RepairOrder>, RepairOrderMut<'a>)> + 'a {
+        self.engine
+            .iter_mut()
+            .map(|engine| RepairOrderMut { engine })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
     }
 }
 
@@ -366,6 +2026,54 @@ impl VesselTransit {
         self.insert(&id, row);
         id
     }
+
+    pub fn remove(&mut self, id: &Valid<VesselTransit>) {
+        self.position.clear(id);
+    }
+
+    pub fn get(&self, id: &Valid<VesselTransit>) -> VesselTransitRef {
+        VesselTransitRef {
+            departure: &self.departure[id],
+            arrival: &self.arrival[id],
+            position: &self.position[id],
+            from: &self.from[id],
+            to: &self.to[id],
+        }
+    }
+
+    pub fn iter_living<'a>(&'a self, alloc: &'a GenAllocator<VesselTransit>) -> impl Iterator<Item = (Valid<'a, VesselTransit>, VesselTransitRef<'a>)> + 'a {
+        self.departure
+            .iter()
+            .zip(self.arrival.iter())
+            .zip(self.position.iter())
+            .zip(self.from.iter())
+            .zip(self.to.iter())
+            .map(|((((departure, arrival), position), from), to)| VesselTransitRef { departure, arrival, position, from, to })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
+
+    pub fn get_mut(&mut self, id: &Valid<VesselTransit>) -> VesselTransitMut {
+        VesselTransitMut {
+            departure: &mut self.departure[id],
+            arrival: &mut self.arrival[id],
+            position: &mut self.position[id],
+            from: &mut self.from[id],
+            to: &mut self.to[id],
+        }
+    }
+
+    pub fn iter_living_mut<'a>(&'a mut self, alloc: &'a GenAllocator<VesselTransit>) -> impl Iterator<Item = (Valid<'a, VesselTransit>, VesselTransitMut<'a>)> + 'a {
+        self.departure
+            .iter_mut()
+            .zip(self.arrival.iter_mut())
+            .zip(self.position.iter_mut())
+            .zip(self.from.iter_mut())
+            .zip(self.to.iter_mut())
+            .map(|((((departure, arrival), position), from), to)| VesselTransitMut { departure, arrival, position, from, to })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
 }
 
 
@@ -387,6 +2095,42 @@ impl VesselOrbit {
         self.insert(&id, row);
         id
     }
+
+    pub fn remove(&mut self, id: &Valid<VesselOrbit>) {
+        self.parent.remove(id);
+    }
+
+    pub fn get(&self, id: &Valid<VesselOrbit>) -> VesselOrbitRef {
+        VesselOrbitRef {
+            parent: &self.parent[id],
+            period: &self.period[id],
+        }
+    }
+
+    pub fn iter_living<'a>(&'a self, alloc: &'a GenAllocator<VesselOrbit>) -> impl Iterator<Item = (Valid<'a, VesselOrbit>, VesselOrbitRef<'a>)> + 'a {
+        self.parent
+            .iter()
+            .zip(self.period.iter())
+            .map(|(parent, period)| VesselOrbitRef { parent, period })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
+
+    pub fn get_mut(&mut self, id: &Valid<VesselOrbit>) -> VesselOrbitMut {
+        VesselOrbitMut {
+            parent: &mut self.parent[id],
+            period: &mut self.period[id],
+        }
+    }
+
+    pub fn iter_living_mut<'a>(&'a mut self, alloc: &'a GenAllocator<VesselOrbit>) -> impl Iterator<Item = (Valid<'a, VesselOrbit>, VesselOrbitMut<'a>)> + 'a {
+        self.parent
+            .iter_mut()
+            .zip(self.period.iter_mut())
+            .map(|(parent, period)| VesselOrbitMut { parent, period })
+            .enumerate()
+            .filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))
+    }
 }
 
 
@@ -417,11 +2161,18 @@ pub struct OrbitRow {
 pub struct SurfaceRow {
     pub area: Area,
     pub albedo: Albedo,
+    pub atmosphere: Option<Atmosphere>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MineSiteRow {
+    pub capacity: Mass,
 }
 
 #[derive(Debug, Clone)]
 pub struct NationRow {
     pub name: String,
+    pub flag: Flag,
 }
 
 #[derive(Debug, Clone)]
@@ -430,6 +2181,12 @@ pub struct ColonyRow {
     pub nation: GenId<Nation>,
     pub name: String,
     pub population: Population,
+    pub supply_pod: Option<GenId<CargoPod>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TradeRouteRow {
+    pub distance: Length,
 }
 
 #[derive(Debug, Clone)]
@@ -444,6 +2201,22 @@ pub struct EngineRow {
     pub thrust: Force,
 }
 
+#[derive(Debug, Clone)]
+pub struct CargoPodRow {
+    pub cargo: Mass,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContractRow {
+    pub pod: GenId<CargoPod>,
+    pub payment: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepairOrderRow {
+    pub engine: Option<GenId<Engine>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct VesselTransitRow {
     pub departure: Time,
@@ -458,11 +2231,209 @@ pub struct VesselOrbitRow {
     pub period: Time,
 }
 
+#[derive(Debug, Clone)]
+pub struct SystemRef<'a> {
+    pub name: &'a Option<String>,
+    pub position: &'a Position,
+    pub temperature: &'a Temperature,
+    pub radius: &'a Length,
+}
+
+pub struct SystemMut<'a> {
+    pub name: &'a mut Option<String>,
+    pub position: &'a mut Position,
+    pub temperature: &'a mut Temperature,
+    pub radius: &'a mut Length,
+}
+
+#[derive(Debug, Clone)]
+pub struct BodyRef<'a> {
+    pub system: &'a Id<System>,
+    pub name: &'a Option<String>,
+    pub mass: &'a Mass,
+    pub radius: &'a Length,
+    pub position: &'a Position,
+    pub velocity: &'a Velocity,
+}
+
+pub struct BodyMut<'a> {
+    pub system: &'a mut Id<System>,
+    pub name: &'a mut Option<String>,
+    pub mass: &'a mut Mass,
+    pub radius: &'a mut Length,
+    pub velocity: &'a mut Velocity,
+}
+
+#[derive(Debug, Clone)]
+pub struct OrbitRef<'a> {
+    pub parent: &'a Option<Id<Orbit>>,
+    pub period: &'a Time,
+    pub radius: &'a Length,
+    pub relative_position: &'a Position,
+    pub position: &'a Position,
+    pub depth: &'a u32,
+}
+
+pub struct OrbitMut<'a> {
+    pub parent: &'a mut Option<Id<Orbit>>,
+    pub period: &'a mut Time,
+    pub radius: &'a mut Length,
+    pub relative_position: &'a mut Position,
+}
+
+#[derive(Debug, Clone)]
+pub struct SurfaceRef<'a> {
+    pub area: &'a Area,
+    pub albedo: &'a Albedo,
+    pub temperature: &'a Temperature,
+    pub absorption: &'a Absorption,
+}
+
+pub struct SurfaceMut<'a> {
+    pub area: &'a mut Area,
+    pub albedo: &'a mut Albedo,
+    pub temperature: &'a mut Temperature,
+}
+
+#[derive(Debug, Clone)]
+pub struct MineSiteRef<'a> {
+    pub capacity: &'a Mass,
+}
+
+pub struct MineSiteMut<'a> {
+    pub capacity: &'a mut Mass,
+}
+
+#[derive(Debug, Clone)]
+pub struct NationRef<'a> {
+    pub name: &'a String,
+    pub flag: &'a Flag,
+    pub population: &'a Population,
+}
+
+pub struct NationMut<'a> {
+    pub name: &'a mut String,
+    pub flag: &'a mut Flag,
+    pub population: &'a mut Population,
+}
+
+#[derive(Debug, Clone)]
+pub struct ColonyRef<'a> {
+    pub body: &'a Id<Body>,
+    pub nation: &'a GenId<Nation>,
+    pub name: &'a String,
+    pub population: &'a Population,
+    pub supply_pod: &'a Option<GenId<CargoPod>>,
+}
+
+pub struct ColonyMut<'a> {
+    pub body: &'a mut Id<Body>,
+    pub nation: &'a mut GenId<Nation>,
+    pub name: &'a mut String,
+    pub population: &'a mut Population,
+    pub supply_pod: &'a mut Option<GenId<CargoPod>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TradeRouteRef<'a> {
+    pub distance: &'a Length,
+}
+
+pub struct TradeRouteMut<'a> {
+    pub distance: &'a mut Length,
+}
+
+#[derive(Debug, Clone)]
+pub struct VesselRef<'a> {
+    pub name: &'a String,
+    pub mass: &'a Mass,
+    pub speed: &'a Speed,
+    pub registry: &'a Registry,
+    pub target: &'a Option<Id<Body>>,
+    pub home: &'a Option<GenId<Colony>>,
+}
+
+pub struct VesselMut<'a> {
+    pub name: &'a mut String,
+    pub mass: &'a mut Mass,
+    pub speed: &'a mut Speed,
+    pub registry: &'a mut Registry,
+    pub target: &'a mut Option<Id<Body>>,
+    pub home: &'a mut Option<GenId<Colony>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EngineRef<'a> {
+    pub thrust: &'a Force,
+}
+
+pub struct EngineMut<'a> {
+    pub thrust: &'a mut Force,
+}
+
+#[derive(Debug, Clone)]
+pub struct CargoPodRef<'a> {
+    pub cargo: &'a Mass,
+}
+
+pub struct CargoPodMut<'a> {
+    pub cargo: &'a mut Mass,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContractRef<'a> {
+    pub pod: &'a GenId<CargoPod>,
+    pub payment: &'a f64,
+}
+
+pub struct ContractMut<'a> {
+    pub pod: &'a mut GenId<CargoPod>,
+    pub payment: &'a mut f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepairOrderRef<'a> {
+    pub engine: &'a Option<GenId<Engine>>,
+}
+
+pub struct RepairOrderMut<'a> {
+    pub engine: &'a mut Option<GenId<Engine>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VesselTransitRef<'a> {
+    pub departure: &'a Time,
+    pub arrival: &'a Time,
+    pub position: &'a Position,
+    pub from: &'a Id<Body>,
+    pub to: &'a Id<Body>,
+}
+
+pub struct VesselTransitMut<'a> {
+    pub departure: &'a mut Time,
+    pub arrival: &'a mut Time,
+    pub position: &'a mut Position,
+    pub from: &'a mut Id<Body>,
+    pub to: &'a mut Id<Body>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VesselOrbitRef<'a> {
+    pub parent: &'a Option<Id<Body>>,
+    pub period: &'a Time,
+}
+
+pub struct VesselOrbitMut<'a> {
+    pub parent: &'a mut Option<Id<Body>>,
+    pub period: &'a mut Time,
+}
+
 #[derive(Debug, Clone)]
 pub struct BodyEntity {
     pub body: BodyRow,
     pub orbit: Option<OrbitRow>,
     pub surface: Option<SurfaceRow>,
+    pub mine_site: Vec<MineSiteRow>,
 }
 
 
@@ -470,6 +2441,7 @@ pub struct BodyEntity {
 pub struct VesselEntity {
     pub vessel: VesselRow,
     pub engine: Option<EngineRow>,
+    pub cargo_pod: Vec<CargoPodRow>,
     pub vessel_location: VesselLocationRow,
 }
 
@@ -480,6 +2452,18 @@ pub enum VesselLocationRow {
     VesselTransit(VesselTransitRow),
 }
 
+impl From<VesselOrbitRow> for VesselLocationRow {
+    fn from(value: VesselOrbitRow) -> Self {
+        VesselLocationRow::VesselOrbit(value)
+    }
+}
+
+impl From<VesselTransitRow> for VesselLocationRow {
+    fn from(value: VesselTransitRow) -> Self {
+        VesselLocationRow::VesselTransit(value)
+    }
+}
+
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum VesselLocation {
@@ -499,6 +2483,18 @@ impl From<GenId<VesselTransit>> for VesselLocation {
     }
 }
 
+impl From<Valid<'_, VesselOrbit>> for VesselLocation {
+    fn from(value: Valid<'_, VesselOrbit>) -> Self {
+        VesselLocation::VesselOrbit(value.id())
+    }
+}
+
+impl From<Valid<'_, VesselTransit>> for VesselLocation {
+    fn from(value: Valid<'_, VesselTransit>) -> Self {
+        VesselLocation::VesselTransit(value.id())
+    }
+}
+
 
 #[derive(Debug, Default, Copy, Clone)]
 pub struct Population;
@@ -506,6 +2502,34 @@ pub struct Population;
 pub struct Albedo(f64);
 #[derive(Debug, Default, Copy, Clone)]
 pub struct Starfield;
+#[derive(Debug, Default, Clone)]
+pub struct Atmosphere(Vec<(String, f64)>);
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Absorption(f64);
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Flag(pub char);
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Registry(pub String);
+
+fn get_absorption(albedo: &Albedo) -> Absorption {
+    Absorption(1.0 - albedo.0)
+}
+
+fn get_orbit_position(relative_position: &Position, parent: Option<&Position>) -> Position {
+    let mut position = *relative_position;
+    if let Some(parent) = parent {
+        position += *parent;
+    }
+    position
+}
+
+fn get_orbit_depth(parent: Option<&u32>) -> u32 {
+    parent.map_or(0, |depth| depth + 1)
+}
+
+fn get_body_position(orbit: Option<&Position>) -> Position {
+    orbit.copied().unwrap_or_default()
+}
 
 fn main() {
     let mut world = World::default();
@@ -513,6 +2537,8 @@ fn main() {
     let sol = world.create_system(get_sol());
     let earth = world.create_body(get_earth(sol));
     let _moon = world.create_body(get_luna(&world.state, earth));
+
+    world.update_derived();
 }
 
 fn get_sol() -> SystemRow {
@@ -540,7 +2566,11 @@ fn get_earth(system: Id<System>) -> BodyEntity {
         surface: Some(SurfaceRow {
             area: Area::in_meters_squared(510.1e12),
             albedo: Albedo(0.30),
+            atmosphere: Some(Atmosphere(vec![("N2".to_string(), 0.78), ("O2".to_string(), 0.21)])),
         }),
+        mine_site: vec![MineSiteRow {
+            capacity: Mass::in_kilograms(1e9),
+        }],
     }
 }
 
@@ -562,32 +2592,9 @@ fn get_luna(state: &State, earth: Id<Body>) -> BodyEntity {
         surface: Some(SurfaceRow {
             area: Area::in_meters_squared(38e12),
             albedo: Albedo(0.12),
+            atmosphere: None,
         }),
-    }
-}
-
-pub struct BodyPosition;
-
-impl BodyPosition {
-    pub fn update(state: &mut State) {
-        let position = &mut state.body.position;
-        let orbit = &state.body.orbit;
-
-        let relative_position = &state.orbit.relative_position;
-        let parent = &state.orbit.parent;
-
-        position
-            .iter_mut()
-            .zip(orbit.iter())
-            .filter_map(|(pos, orbit)| orbit.map(|orbit| (pos, orbit)))
-            .for_each(|(pos, orbit)| {
-                let mut orbit = orbit;
-                *pos = relative_position[orbit];
-                while let Some(parent) = parent[orbit] {
-                    orbit = parent;
-                    *pos += relative_position[orbit];
-                }
-            })
+        mine_site: vec![],
     }
 }
 
@@ -1,3 +1,5 @@
+use crate::entities::{get_invalid_relationship, Relationship};
+use crate::errors::SchemaError;
 use crate::lifespans::*;
 use code_gen::Visibility::Pub;
 use code_gen::*;
@@ -12,6 +14,8 @@ pub struct Component {
     pub source: Source,
     pub density: Density,
     pub comp_type: Type,
+    /// Dense columns are only reset by `remove` if their type is known to implement `Default`.
+    pub clearable: bool,
 }
 
 impl Component {
@@ -23,6 +27,19 @@ impl Component {
         Type::new(&format!("Component<{},{}>", arena, self.get_type()))
     }
 
+    // rare components are stored by id rather than by index
+    pub fn get_storage_type(&self, id: &Type) -> Type {
+        match self.density {
+            Density::Rare => Type::new(&format!("ComponentMap<{},{}>", id, self.comp_type)),
+            _ => self.get_component_type(),
+        }
+    }
+
+    // row views borrow from the arena, e.g. `&'a Option<String>`
+    pub fn get_view_type(&self, mutable: bool) -> String {
+        format!("&'a {}{}", if mutable { "mut " } else { "" }, self.get_type())
+    }
+
     pub fn get_row_field(&self) -> Option<Field> {
         self.get_row_type().map(|t| Field {
             visibility: Pub,
@@ -33,7 +50,7 @@ impl Component {
 
     fn get_row_type(&self) -> Option<Type> {
         match self.source {
-            Source::ByDefault => None,
+            Source::ByDefault | Source::ByExpr(_) | Source::Derived { .. } => None,
             Source::ByValue => Some(self.get_type()),
         }
     }
@@ -41,39 +58,179 @@ impl Component {
     fn get_type(&self) -> Type {
         match self.density {
             Density::Dense => self.comp_type.clone(),
-            Density::Sparse => Type::new(&format!("Option<{}>", &self.comp_type)),
+            Density::Sparse | Density::Rare => Type::new(&format!("Option<{}>", &self.comp_type)),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub enum Source {
     ByValue,
     ByDefault,
+    // evaluated on insert while the row is still whole
+    ByExpr(String),
+    // recomputed from other components of the same arena, or of arenas it links to
+    Derived { function: String, inputs: Vec<DerivedInput> },
+}
+
+/// A component read by a derived component, either `field` on the same arena or `link.field` on the linked arena.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DerivedInput {
+    pub link: Option<SnakeCase>,
+    pub field: SnakeCase,
+}
+
+impl Display for DerivedInput {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match &self.link {
+            Some(link) => write!(f, "{}.{}", link, self.field),
+            None => write!(f, "{}", self.field),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Density {
     Dense,
     Sparse,
+    Rare,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ReferentialAction {
+    Cascade,
+    Nullify,
+    Restrict,
+}
+
+#[derive(Debug, Clone)]
+pub struct Reference {
+    pub field: SnakeCase,
+    pub arena: ArenaName,
+    pub optional: bool,
+    pub on_delete: ReferentialAction,
+    /// Overrides the name of the reverse index field generated on the target arena.
+    pub reverse_index: Option<SnakeCase>,
+}
+
+impl Reference {
+    pub fn required(field: SnakeCase, arena: ArenaName) -> Self {
+        Self {
+            field,
+            arena,
+            optional: false,
+            on_delete: ReferentialAction::Restrict,
+            reverse_index: None,
+        }
+    }
+
+    pub fn owner(field: SnakeCase, arena: ArenaName) -> Self {
+        Self {
+            field,
+            arena,
+            optional: false,
+            on_delete: ReferentialAction::Cascade,
+            reverse_index: None,
+        }
+    }
+
+    pub fn optional(field: SnakeCase, arena: ArenaName) -> Self {
+        Self {
+            field,
+            arena,
+            optional: true,
+            on_delete: ReferentialAction::Nullify,
+            reverse_index: None,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct ArenaCore {
     pub name: ArenaName,
     pub components: Vec<Component>,
-    pub refs: Vec<(SnakeCase, ArenaName)>,
-    pub optional_refs: Vec<(SnakeCase, ArenaName)>,
+    pub refs: Vec<Reference>,
+    pub many_refs: Vec<(SnakeCase, ArenaName)>,
+    pub tags: Vec<SnakeCase>,
 }
 
 impl ArenaCore {
     pub fn new(name: &str) -> Self {
-        Self {
-            name: ArenaName::new(name),
+        Self::try_new(name).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_new(name: &str) -> std::result::Result<Self, SchemaError> {
+        Ok(Self {
+            name: ArenaName::try_new(name)?,
             components: vec![],
             refs: vec![],
-            optional_refs: vec![],
+            many_refs: vec![],
+            tags: vec![],
+        })
+    }
+
+    pub fn is_reference(&self, field: &SnakeCase) -> bool {
+        self.refs.iter().any(|r| r.field == *field)
+    }
+
+    fn parse_field(&self, field: &str) -> std::result::Result<SnakeCase, SchemaError> {
+        field.parse().map_err(|_| SchemaError::InvalidFieldName {
+            arena: self.name.to_string(),
+            field: field.to_string(),
+        })
+    }
+
+    // references are looked up by field, e.g. by set_referential_action, so each field may only be used once
+    fn parse_reference_field(&self, field: &str) -> std::result::Result<SnakeCase, SchemaError> {
+        let field = self.parse_field(field)?;
+
+        let exists = self.is_reference(&field)
+            || self.many_refs.iter().any(|(f, _)| *f == field);
+
+        if exists {
+            return Err(SchemaError::DuplicateReference {
+                arena: self.name.to_string(),
+                field: field.to_string(),
+            });
         }
+
+        Ok(field)
+    }
+
+    // inputs are zipped with the derived component, so they must be indexed columns declared before it,
+    // the targets of linked inputs are not known until the world is validated
+    fn parse_derived_input(&self, field: &SnakeCase, input: &str) -> std::result::Result<DerivedInput, SchemaError> {
+        let error = || SchemaError::InvalidDerivedInput {
+            arena: self.name.to_string(),
+            field: field.to_string(),
+            input: input.to_string(),
+        };
+
+        match input.split_once('.') {
+            Some((link, linked)) => Ok(DerivedInput {
+                link: Some(link.parse().map_err(|_| error())?),
+                field: linked.parse().map_err(|_| error())?,
+            }),
+            None => self
+                .components
+                .iter()
+                .find(|c| c.field_name.to_string() == input && c.density != Density::Rare)
+                .map(|c| DerivedInput {
+                    link: None,
+                    field: c.field_name.clone(),
+                })
+                .ok_or_else(error),
+        }
+    }
+
+    fn parse_type_field(&self, type_name: &str) -> std::result::Result<SnakeCase, SchemaError> {
+        CamelCase::from_str(type_name)
+            .map(|cc| cc.into_snake_case())
+            .or_else(|_| SnakeCase::from_str(type_name))
+            .map_err(|_| SchemaError::InvalidTypeField {
+                arena: self.name.to_string(),
+                type_name: type_name.to_string(),
+            })
     }
 }
 
@@ -85,144 +242,377 @@ pub struct Arena<L: Lifespan> {
 
 impl<L: Lifespan> Arena<L> {
     pub fn new(name: &str) -> Self {
-        Self {
-            arena: ArenaCore::new(name),
+        Self::try_new(name).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_new(name: &str) -> std::result::Result<Self, SchemaError> {
+        Ok(Self {
+            arena: ArenaCore::try_new(name)?,
             marker: PhantomData,
-        }
+        })
     }
 
     pub fn add_required_component(&mut self, type_name: &str) {
-        let field = CamelCase::from_str(type_name)
-            .map(|cc| cc.into_snake_case())
-            .or_else(|_| SnakeCase::from_str(type_name))
-            .expect(&format!(
-                "Given type cannot be formatted as snake_case: {}",
-                type_name
-            ));
+        self.try_add_required_component(type_name)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        self.arena.components.push(Component {
-            field_name: field.clone(),
-            source: Source::ByValue,
-            density: Density::Dense,
-            comp_type: Type::new(type_name),
-        });
+    pub fn try_add_required_component(&mut self, type_name: &str) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_type_field(type_name)?;
+        self.push_component(field, Density::Dense, Source::ByValue, Type::new(type_name));
+        Ok(())
     }
 
     pub fn add_required_component_with_field(&mut self, field: &str, type_name: &str) {
-        self.arena.components.push(Component {
-            field_name: field
-                .parse()
-                .expect(&format!("Fields must be in snake_case: {}", field)),
-            source: Source::ByValue,
-            density: Density::Dense,
-            comp_type: Type::new(type_name),
-        });
+        self.try_add_required_component_with_field(field, type_name)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_required_component_with_field(&mut self, field: &str, type_name: &str) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_field(field)?;
+        self.push_component(field, Density::Dense, Source::ByValue, Type::new(type_name));
+        Ok(())
     }
 
     pub fn add_optional_component(&mut self, type_name: &str) {
-        let field = CamelCase::from_str(type_name)
-            .map(|cc| cc.into_snake_case())
-            .or_else(|_| SnakeCase::from_str(type_name))
-            .expect(&format!(
-                "Given type cannot be formatted as snake_case: {}",
-                type_name
-            ));
+        self.try_add_optional_component(type_name)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        self.arena.components.push(Component {
-            field_name: field.clone(),
-            source: Source::ByValue,
-            density: Density::Sparse,
-            comp_type: Type::new(type_name),
-        });
+    pub fn try_add_optional_component(&mut self, type_name: &str) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_type_field(type_name)?;
+        self.push_component(field, Density::Sparse, Source::ByValue, Type::new(type_name));
+        Ok(())
     }
 
     pub fn add_optional_component_with_field(&mut self, field: &str, type_name: &str) {
-        self.arena.components.push(Component {
-            field_name: field
-                .parse()
-                .expect(&format!("Fields must be in snake_case: {}", field)),
-            source: Source::ByValue,
-            density: Density::Sparse,
-            comp_type: Type::new(type_name),
-        });
+        self.try_add_optional_component_with_field(field, type_name)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_optional_component_with_field(&mut self, field: &str, type_name: &str) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_field(field)?;
+        self.push_component(field, Density::Sparse, Source::ByValue, Type::new(type_name));
+        Ok(())
+    }
+
+    /// Rare components are stored in a `ComponentMap` keyed by id. They appear as `Option<T>` in the arena row and
+    /// entity read-back, but are left out of the `Ref`/`Mut` row views and reference joins; look them up by id instead.
+    pub fn add_rare_component(&mut self, type_name: &str) {
+        self.try_add_rare_component(type_name)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_rare_component(&mut self, type_name: &str) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_type_field(type_name)?;
+        self.push_component(field, Density::Rare, Source::ByValue, Type::new(type_name));
+        Ok(())
+    }
+
+    pub fn add_rare_component_with_field(&mut self, field: &str, type_name: &str) {
+        self.try_add_rare_component_with_field(field, type_name)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_rare_component_with_field(&mut self, field: &str, type_name: &str) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_field(field)?;
+        self.push_component(field, Density::Rare, Source::ByValue, Type::new(type_name));
+        Ok(())
+    }
+
+    pub fn add_tag(&mut self, field: &str) {
+        self.try_add_tag(field)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_tag(&mut self, field: &str) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_field(field)?;
+        self.arena.tags.push(field);
+        Ok(())
     }
 
     pub fn add_default_component(&mut self, type_name: &str) {
-        let field = CamelCase::from_str(type_name)
-            .map(|cc| cc.into_snake_case())
-            .or_else(|_| SnakeCase::from_str(type_name))
-            .expect(&format!(
-                "Given type cannot be formatted as snake_case: {}",
-                type_name
-            ));
+        self.try_add_default_component(type_name)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        self.arena.components.push(Component {
-            field_name: field.clone(),
-            source: Source::ByDefault,
-            density: Density::Dense,
-            comp_type: Type::new(type_name),
-        });
+    pub fn try_add_default_component(&mut self, type_name: &str) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_type_field(type_name)?;
+        self.push_component(field, Density::Dense, Source::ByDefault, Type::new(type_name));
+        Ok(())
     }
 
     pub fn add_default_component_with_field(&mut self, field: &str, type_name: &str) {
-        self.arena.components.push(Component {
-            field_name: field
-                .parse()
-                .expect(&format!("Fields must be in snake_case: {}", field)),
-            source: Source::ByDefault,
-            density: Density::Dense,
-            comp_type: Type::new(type_name),
-        });
+        self.try_add_default_component_with_field(field, type_name)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_default_component_with_field(&mut self, field: &str, type_name: &str) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_field(field)?;
+        self.push_component(field, Density::Dense, Source::ByDefault, Type::new(type_name));
+        Ok(())
+    }
+
+    pub fn add_default_component_with_expr(&mut self, field: &str, type_name: &str, expr: &str) {
+        self.try_add_default_component_with_expr(field, type_name, expr)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_default_component_with_expr(&mut self, field: &str, type_name: &str, expr: &str) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_field(field)?;
+        self.push_component(field, Density::Dense, Source::ByExpr(expr.to_string()), Type::new(type_name));
+        Ok(())
+    }
+
+    /// `init` is a closure taking the arena row by reference, e.g. `|row| row.radius * 2.0`
+    pub fn add_default_component_with_init(&mut self, field: &str, type_name: &str, init: &str) {
+        self.try_add_default_component_with_init(field, type_name, init)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_default_component_with_init(&mut self, field: &str, type_name: &str, init: &str) -> std::result::Result<(), SchemaError> {
+        let expr = format!("(({}) as fn(&{}) -> {})(&row)", init, self.name().get_row_type(), type_name);
+        self.try_add_default_component_with_expr(field, type_name, &expr)
+    }
+
+    /// `function` takes each input by reference, e.g. `&["radius"]`. An input behind a link, e.g. `"parent.position"`,
+    /// is passed as `Option<&T>` if the link is optional. Components with linked inputs start out as `Default::default()`
+    /// and are filled in by `World::update_derived`, which visits an optional self link parent first.
+    pub fn add_derived_component(&mut self, type_name: &str, function: &str, inputs: &[&str]) {
+        self.try_add_derived_component(type_name, function, inputs)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_derived_component(&mut self, type_name: &str, function: &str, inputs: &[&str]) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_type_field(type_name)?;
+        self.push_derived_component(field, type_name, function, inputs)
+    }
+
+    pub fn add_derived_component_with_field(&mut self, field: &str, type_name: &str, function: &str, inputs: &[&str]) {
+        self.try_add_derived_component_with_field(field, type_name, function, inputs)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_derived_component_with_field(&mut self, field: &str, type_name: &str, function: &str, inputs: &[&str]) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_field(field)?;
+        self.push_derived_component(field, type_name, function, inputs)
+    }
+
+    fn push_derived_component(&mut self, field: SnakeCase, type_name: &str, function: &str, inputs: &[&str]) -> std::result::Result<(), SchemaError> {
+        let inputs = inputs
+            .iter()
+            .map(|input| self.arena.parse_derived_input(&field, input))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let source = Source::Derived {
+            function: function.to_string(),
+            inputs,
+        };
+
+        self.push_component(field, Density::Dense, source, Type::new(type_name));
+        Ok(())
     }
 
     pub fn add_optional_self_link(&mut self, field: &str) {
-        self.arena.components.push(Component {
-            field_name: field
-                .parse()
-                .expect(&format!("Fields must be in snake_case: {}", field)),
-            source: Source::ByValue,
-            density: Density::Sparse,
-            comp_type: self.id_type(),
-        });
+        self.try_add_optional_self_link(field)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_optional_self_link(&mut self, field: &str) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_reference_field(field)?;
+
+        self.arena.refs.push(Reference::optional(field.clone(), self.name()));
+        self.push_component(field, Density::Sparse, Source::ByValue, self.id_type());
+        Ok(())
     }
 
+    // Transient -> Transient references must go through add_owner_reference
     pub fn add_reference(&mut self, arena: &Arena<impl Lifespan>) {
-        self.arena.components.push(Component {
-            field_name: arena.name().as_field_name(),
-            source: Source::ByValue,
-            density: Density::Dense,
-            comp_type: arena.id_type(),
-        });
+        self.try_add_reference(arena)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_reference(&mut self, arena: &Arena<impl Lifespan>) -> std::result::Result<(), SchemaError> {
+        self.try_add_reference_with_field(&arena.name().as_field_name().to_string(), arena)
     }
 
     pub fn add_reference_with_field(&mut self, field: &str, arena: &Arena<impl Lifespan>) {
-        self.arena.components.push(Component {
-            field_name: field
-                .parse()
-                .expect(&format!("Fields must be in snake_case: {}", field)),
-            source: Source::ByValue,
-            density: Density::Dense,
-            comp_type: arena.id_type(),
-        });
+        self.try_add_reference_with_field(field, arena)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_reference_with_field(&mut self, field: &str, arena: &Arena<impl Lifespan>) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_reference_field(field)?;
+        self.check_relationship(&field, arena, Relationship::Ref)?;
+
+        self.arena.refs.push(Reference::required(field.clone(), arena.name()));
+        self.push_component(field, Density::Dense, Source::ByValue, arena.id_type());
+        Ok(())
     }
 
     pub fn add_optional_reference(&mut self, arena: &Arena<impl Lifespan>) {
-        self.arena.components.push(Component {
-            field_name: arena.name().as_field_name(),
-            source: Source::ByValue,
-            density: Density::Sparse,
-            comp_type: arena.id_type(),
-        });
+        self.try_add_optional_reference(arena)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_optional_reference(&mut self, arena: &Arena<impl Lifespan>) -> std::result::Result<(), SchemaError> {
+        self.try_add_optional_reference_with_field(&arena.name().as_field_name().to_string(), arena)
     }
 
     pub fn add_optional_reference_with_field(&mut self, field: &str, arena: &Arena<impl Lifespan>) {
+        self.try_add_optional_reference_with_field(field, arena)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_optional_reference_with_field(&mut self, field: &str, arena: &Arena<impl Lifespan>) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_reference_field(field)?;
+        self.check_relationship(&field, arena, Relationship::MaybeRef)?;
+
+        self.arena.refs.push(Reference::optional(field.clone(), arena.name()));
+        self.push_component(field, Density::Sparse, Source::ByValue, arena.id_type());
+        Ok(())
+    }
+
+    pub fn add_late_reference(&mut self, arena: &Arena<impl Lifespan>) {
+        self.try_add_late_reference(arena)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_late_reference(&mut self, arena: &Arena<impl Lifespan>) -> std::result::Result<(), SchemaError> {
+        self.try_add_late_reference_with_field(&arena.name().as_field_name().to_string(), arena)
+    }
+
+    pub fn add_late_reference_with_field(&mut self, field: &str, arena: &Arena<impl Lifespan>) {
+        self.try_add_late_reference_with_field(field, arena)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_late_reference_with_field(&mut self, field: &str, arena: &Arena<impl Lifespan>) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_reference_field(field)?;
+        self.check_relationship(&field, arena, Relationship::MaybeRef)?;
+        self.arena.refs.push(Reference::optional(field.clone(), arena.name()));
+        self.push_component(field, Density::Sparse, Source::ByDefault, arena.id_type());
+        Ok(())
+    }
+
+    pub fn add_many_reference(&mut self, arena: &Arena<impl Lifespan>) {
+        self.try_add_many_reference(arena)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_many_reference(&mut self, arena: &Arena<impl Lifespan>) -> std::result::Result<(), SchemaError> {
+        self.try_add_many_reference_with_field(&arena.name().as_field_name().to_string(), arena)
+    }
+
+    pub fn add_many_reference_with_field(&mut self, field: &str, arena: &Arena<impl Lifespan>) {
+        self.try_add_many_reference_with_field(field, arena)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_many_reference_with_field(&mut self, field: &str, arena: &Arena<impl Lifespan>) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_reference_field(field)?;
+        self.check_relationship(&field, arena, Relationship::ManyRef)?;
+        self.arena.many_refs.push((field, arena.name()));
+        Ok(())
+    }
+
+    pub fn set_referential_action(&mut self, field: &str, action: ReferentialAction) {
+        self.try_set_referential_action(field, action)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_set_referential_action(&mut self, field: &str, action: ReferentialAction) -> std::result::Result<(), SchemaError> {
+        let arena = self.arena.name.to_string();
+
+        let reference = self
+            .arena
+            .refs
+            .iter_mut()
+            .find(|r| r.field.to_string() == field)
+            .ok_or_else(|| SchemaError::ReferenceNotFound {
+                arena: arena.clone(),
+                field: field.to_string(),
+            })?;
+
+        if action == ReferentialAction::Nullify && !reference.optional {
+            return Err(SchemaError::NullifyRequiredReference {
+                arena,
+                field: field.to_string(),
+            });
+        }
+
+        reference.on_delete = action;
+        Ok(())
+    }
+
+    /// Renames the reverse index that the reference adds to its target arena, e.g. to resolve a `FieldCollision`.
+    pub fn set_reverse_index(&mut self, field: &str, name: &str) {
+        self.try_set_reverse_index(field, name)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_set_reverse_index(&mut self, field: &str, name: &str) -> std::result::Result<(), SchemaError> {
+        let name = self.arena.parse_field(name)?;
+        let arena = self.arena.name.to_string();
+
+        let reference = self
+            .arena
+            .refs
+            .iter_mut()
+            .find(|r| r.field.to_string() == field)
+            .ok_or_else(|| SchemaError::ReferenceNotFound {
+                arena,
+                field: field.to_string(),
+            })?;
+
+        reference.reverse_index = Some(name);
+        Ok(())
+    }
+
+    /// Resets a required component to `Default::default()` when its row is removed, e.g. to free a `String` name.
+    pub fn set_clearable(&mut self, field: &str) {
+        self.try_set_clearable(field)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_set_clearable(&mut self, field: &str) -> std::result::Result<(), SchemaError> {
+        let arena = self.arena.name.to_string();
+
+        let component = self
+            .arena
+            .components
+            .iter_mut()
+            .find(|c| c.field_name.to_string() == field)
+            .ok_or_else(|| SchemaError::ComponentNotFound {
+                arena,
+                field: field.to_string(),
+            })?;
+
+        component.clearable = true;
+        Ok(())
+    }
+
+    // see the ownership matrix in entities.rs
+    fn check_relationship<T: Lifespan>(&self, field: &SnakeCase, arena: &Arena<T>, relationship: Relationship) -> std::result::Result<(), SchemaError> {
+        match get_invalid_relationship(L::is_deletable(), T::is_deletable(), relationship) {
+            Some(reason) => Err(SchemaError::InvalidReference {
+                arena: self.arena.name.to_string(),
+                field: field.to_string(),
+                target: arena.name().to_string(),
+                reason: reason.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn push_component(&mut self, field_name: SnakeCase, density: Density, source: Source, comp_type: Type) {
+        let clearable = source == Source::ByDefault;
         self.arena.components.push(Component {
-            field_name: field
-                .parse()
-                .expect(&format!("Fields must be in snake_case: {}", field)),
-            source: Source::ByValue,
-            density: Density::Sparse,
-            comp_type: arena.id_type(),
+            field_name,
+            source,
+            density,
+            comp_type,
+            clearable,
         });
     }
 
@@ -243,14 +633,45 @@ impl<L: Lifespan> Arena<L> {
     }
 }
 
+// a permanent arena cannot hold a required reference to a transient arena, it could not be unlinked when the target is removed
+impl Arena<Transient> {
+    // the referenced arena owns this one, which is deleted along with it
+    pub fn add_owner_reference(&mut self, arena: &Arena<Transient>) {
+        self.try_add_owner_reference(arena)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_owner_reference(&mut self, arena: &Arena<Transient>) -> std::result::Result<(), SchemaError> {
+        self.try_add_owner_reference_with_field(&arena.name().as_field_name().to_string(), arena)
+    }
+
+    pub fn add_owner_reference_with_field(&mut self, field: &str, arena: &Arena<Transient>) {
+        self.try_add_owner_reference_with_field(field, arena)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_owner_reference_with_field(&mut self, field: &str, arena: &Arena<Transient>) -> std::result::Result<(), SchemaError> {
+        let field = self.arena.parse_reference_field(field)?;
+        self.check_relationship(&field, arena, Relationship::OwnerRef)?;
+
+        self.arena.refs.push(Reference::owner(field.clone(), arena.name()));
+        self.push_component(field, Density::Dense, Source::ByValue, arena.id_type());
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct ArenaName(CamelCase);
 
 impl ArenaName {
     pub fn new(s: &str) -> Self {
+        Self::try_new(s).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_new(s: &str) -> std::result::Result<Self, SchemaError> {
         s.parse()
             .map(Self)
-            .expect(&format!("Arena names must be in CamelCase: {}", s))
+            .map_err(|_| SchemaError::InvalidArenaName { name: s.to_string() })
     }
 
     pub fn as_field_name(&self) -> SnakeCase {
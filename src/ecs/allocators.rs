@@ -18,6 +18,10 @@ impl<T> FixedAllocator<T> {
     pub fn ids(&self) -> impl Iterator<Item = Id<T>> {
         (0..self.next_index).into_iter().map(|i| Id::new(i))
     }
+
+    pub(crate) fn len(&self) -> usize {
+        self.next_index
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -80,11 +84,24 @@ impl<T> GenAllocator<T> {
         }
     }
 
+    pub fn validate_index(&self, index: usize) -> Option<Valid<T>> {
+        if !self.living.contains(index) {
+            return None;
+        }
+
+        let gen = self.generation.get(index)?;
+        let id = GenId::new(index, *gen);
+        Some(Valid::new(id))
+    }
+
     pub fn ids<'a>(&'a self) -> impl Iterator<Item = Valid<T>> + 'a {
-        self.living.iter().filter_map(move |index| {
-            let gen = self.generation.get(index)?;
-            let id = GenId::new(index, *gen);
-            Some(Valid::new(id))
-        })
+        self.living
+            .iter()
+            .filter_map(move |index| self.validate_index(index))
+    }
+
+    // includes dead slots
+    pub(crate) fn len(&self) -> usize {
+        self.generation.len()
     }
 }
@@ -1,4 +1,5 @@
 use super::{Get, GetOpt, Insert};
+use crate::ecs::allocators::GenAllocator;
 use crate::ecs::ids::{Id, Valid};
 use rayon::iter::*;
 use std::marker::PhantomData;
@@ -34,6 +35,76 @@ impl<ID, T> Component<ID, T> {
     pub fn iter_mut(&mut self) -> std::slice::IterMut<T> {
         (&mut self.values.values).into_iter()
     }
+
+    /// Counts the allocated slots, including those of killed ids.
+    pub fn len(&self) -> usize {
+        self.values.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+// skips the slots of killed ids
+impl<ID, T> Component<ID, T> {
+    pub fn iter_living<'a>(
+        &'a self,
+        alloc: &'a GenAllocator<ID>,
+    ) -> impl Iterator<Item = (Valid<'a, ID>, &'a T)> + 'a {
+        self.iter()
+            .enumerate()
+            .filter_map(move |(i, value)| alloc.validate_index(i).map(|id| (id, value)))
+    }
+
+    pub fn iter_living_mut<'a>(
+        &'a mut self,
+        alloc: &'a GenAllocator<ID>,
+    ) -> impl Iterator<Item = (Valid<'a, ID>, &'a mut T)> + 'a {
+        self.iter_mut()
+            .enumerate()
+            .filter_map(move |(i, value)| alloc.validate_index(i).map(|id| (id, value)))
+    }
+}
+
+impl<ID: Send + Sync, T: Send + Sync> Component<ID, T> {
+    pub fn par_iter_living<'a>(
+        &'a self,
+        alloc: &'a GenAllocator<ID>,
+    ) -> impl ParallelIterator<Item = (Valid<'a, ID>, &'a T)> + 'a {
+        self.values
+            .values
+            .par_iter()
+            .enumerate()
+            .filter_map(move |(i, value)| alloc.validate_index(i).map(|id| (id, value)))
+    }
+
+    pub fn par_iter_living_mut<'a>(
+        &'a mut self,
+        alloc: &'a GenAllocator<ID>,
+    ) -> impl ParallelIterator<Item = (Valid<'a, ID>, &'a mut T)> + 'a {
+        self.values
+            .values
+            .par_iter_mut()
+            .enumerate()
+            .filter_map(move |(i, value)| alloc.validate_index(i).map(|id| (id, value)))
+    }
+}
+
+impl<ID, T: Default> Component<ID, T> {
+    #[inline(always)]
+    pub fn clear(&mut self, id: &Valid<ID>) {
+        if let Some(value) = self.values.get_mut(id.id.index) {
+            *value = Default::default();
+        }
+    }
+}
+
+impl<ID, T> Component<ID, Option<T>> {
+    #[inline(always)]
+    pub fn remove(&mut self, id: &Valid<ID>) -> Option<T> {
+        self.values.get_mut(id.id.index).and_then(|o| o.take())
+    }
 }
 
 impl<ID, T> GetOpt<Id<ID>, T> for Component<ID, Option<T>> {
@@ -259,4 +330,46 @@ mod tests {
 
         a.iter().for_each(|a| assert_eq!(20, *a));
     }
+
+    #[test]
+    fn remove_and_clear() {
+        let mut alloc = crate::ecs::GenAllocator::<()>::default();
+        let mut names = Component::<(), String>::default();
+        let mut parent = Component::<(), Option<usize>>::default();
+
+        let id = alloc.create();
+        names.insert(&id, "name".to_string());
+        parent.insert(&id, Some(1));
+
+        names.clear(&id);
+        assert_eq!("", names[&id]);
+
+        assert_eq!(Some(1), parent.remove(&id));
+        assert_eq!(None, parent[&id]);
+    }
+
+    #[test]
+    fn iter_living() {
+        let mut alloc = crate::ecs::GenAllocator::<()>::default();
+        let mut speed = Component::<(), usize>::default();
+
+        let ids: Vec<_> = (0..4)
+            .map(|i| {
+                let id = alloc.create();
+                speed.insert(&id, i);
+                id.id()
+            })
+            .collect();
+
+        alloc.kill(ids[1]);
+
+        speed.iter_living_mut(&alloc).for_each(|(_, s)| *s += 10);
+        assert_eq!(vec![10, 1, 12, 13], speed.iter().copied().collect::<Vec<_>>());
+
+        let living: usize = speed.par_iter_living(&alloc).map(|(_, s)| *s).sum();
+        assert_eq!(35, living);
+
+        let living: Vec<_> = speed.iter_living(&alloc).map(|(id, _)| id.id()).collect();
+        assert_eq!(vec![ids[0], ids[2], ids[3]], living);
+    }
 }
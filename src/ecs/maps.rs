@@ -1,6 +1,9 @@
-use super::{Id, Valid, Get};
+use super::{Component, GenAllocator, Get, GetOpt, Id, Valid};
+use rayon::iter::*;
 use rustc_hash::FxHashMap;
+use std::collections::hash_map::Entry;
 use std::hash::Hash;
+use std::ops::{Index, IndexMut};
 use crate::ecs::{GenId, Insert};
 
 #[derive(Debug, Clone)]
@@ -25,6 +28,102 @@ impl<ID: Hash + Eq, T> ComponentMap<ID, T> {
     pub fn remove(&mut self, id: &ID) -> Option<T> {
         self.values.remove(id)
     }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn contains(&self, id: &ID) -> bool {
+        self.values.contains_key(id)
+    }
+
+    #[inline(always)]
+    pub fn entry(&mut self, id: ID) -> Entry<'_, ID, T> {
+        self.values.entry(id)
+    }
+
+    #[inline(always)]
+    pub fn iter(&self) -> impl Iterator<Item = (&ID, &T)> {
+        self.values.iter()
+    }
+
+    #[inline(always)]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&ID, &mut T)> {
+        self.values.iter_mut()
+    }
+
+    #[inline(always)]
+    pub fn retain(&mut self, f: impl FnMut(&ID, &mut T) -> bool) {
+        self.values.retain(f)
+    }
+
+    #[inline(always)]
+    pub fn drain(&mut self) -> impl Iterator<Item = (ID, T)> + '_ {
+        self.values.drain()
+    }
+
+    #[inline(always)]
+    pub fn clear(&mut self) {
+        self.values.clear()
+    }
+}
+
+impl<ID: Hash + Eq + Send + Sync, T: Send + Sync> ComponentMap<ID, T> {
+    #[inline(always)]
+    pub fn par_iter(&self) -> impl ParallelIterator<Item = (&ID, &T)> {
+        self.values.par_iter()
+    }
+
+    #[inline(always)]
+    pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = (&ID, &mut T)> {
+        self.values.par_iter_mut()
+    }
+}
+
+// the map drives the join, so only ids present in the map are visited
+impl<ID, T> ComponentMap<Id<ID>, T> {
+    pub fn join<'a, C>(&'a self, component: &'a Component<ID, C>) -> impl Iterator<Item = (Id<ID>, &'a T, &'a C)> + 'a {
+        self.iter().map(move |(id, value)| (*id, value, &component[id]))
+    }
+
+    pub fn join_mut<'a, C>(&'a mut self, component: &'a Component<ID, C>) -> impl Iterator<Item = (Id<ID>, &'a mut T, &'a C)> + 'a {
+        self.iter_mut().map(move |(id, value)| (*id, value, &component[id]))
+    }
+}
+
+impl<ID, T> ComponentMap<GenId<ID>, T> {
+    pub fn join<'a, C>(
+        &'a self,
+        component: &'a Component<ID, C>,
+        alloc: &'a GenAllocator<ID>,
+    ) -> impl Iterator<Item = (Valid<'a, ID>, &'a T, &'a C)> + 'a {
+        self.iter()
+            .filter_map(move |(id, value)| alloc.validate(*id).map(|id| (id, value)))
+            .map(move |(id, value)| {
+                let other = &component[&id];
+                (id, value, other)
+            })
+    }
+
+    pub fn join_mut<'a, C>(
+        &'a mut self,
+        component: &'a Component<ID, C>,
+        alloc: &'a GenAllocator<ID>,
+    ) -> impl Iterator<Item = (Valid<'a, ID>, &'a mut T, &'a C)> + 'a {
+        self.iter_mut()
+            .filter_map(move |(id, value)| alloc.validate(*id).map(|id| (id, value)))
+            .map(move |(id, value)| {
+                let other = &component[&id];
+                (id, value, other)
+            })
+    }
 }
 
 impl<ID, T> Get<Id<ID>, T> for ComponentMap<Id<ID>, T> {
@@ -75,6 +174,110 @@ impl<ID, T> Get<&Valid<'_, ID>, T> for ComponentMap<GenId<ID>, T> {
     }
 }
 
+impl<ID, T> GetOpt<Id<ID>, T> for ComponentMap<Id<ID>, T> {
+    #[inline(always)]
+    fn get_opt(&self, id: Id<ID>) -> Option<&T> {
+        self.get(id)
+    }
+
+    #[inline(always)]
+    fn get_opt_mut(&mut self, id: Id<ID>) -> Option<&mut T> {
+        self.get_mut(id)
+    }
+}
+
+impl<ID, T> GetOpt<&Id<ID>, T> for ComponentMap<Id<ID>, T> {
+    #[inline(always)]
+    fn get_opt(&self, id: &Id<ID>) -> Option<&T> {
+        self.get(id)
+    }
+
+    #[inline(always)]
+    fn get_opt_mut(&mut self, id: &Id<ID>) -> Option<&mut T> {
+        self.get_mut(id)
+    }
+}
+
+impl<ID, T> GetOpt<Valid<'_, ID>, T> for ComponentMap<GenId<ID>, T> {
+    #[inline(always)]
+    fn get_opt(&self, id: Valid<'_, ID>) -> Option<&T> {
+        self.get(id)
+    }
+
+    #[inline(always)]
+    fn get_opt_mut(&mut self, id: Valid<'_, ID>) -> Option<&mut T> {
+        self.get_mut(id)
+    }
+}
+
+impl<ID, T> GetOpt<&Valid<'_, ID>, T> for ComponentMap<GenId<ID>, T> {
+    #[inline(always)]
+    fn get_opt(&self, id: &Valid<'_, ID>) -> Option<&T> {
+        self.get(id)
+    }
+
+    #[inline(always)]
+    fn get_opt_mut(&mut self, id: &Valid<'_, ID>) -> Option<&mut T> {
+        self.get_mut(id)
+    }
+}
+
+impl<ID, T> Index<Id<ID>> for ComponentMap<Id<ID>, T> {
+    type Output = T;
+
+    fn index(&self, index: Id<ID>) -> &Self::Output {
+        self.get(index).expect("Id not found in ComponentMap")
+    }
+}
+
+impl<ID, T> IndexMut<Id<ID>> for ComponentMap<Id<ID>, T> {
+    fn index_mut(&mut self, index: Id<ID>) -> &mut Self::Output {
+        self.get_mut(index).expect("Id not found in ComponentMap")
+    }
+}
+
+impl<ID, T> Index<&Id<ID>> for ComponentMap<Id<ID>, T> {
+    type Output = T;
+
+    fn index(&self, index: &Id<ID>) -> &Self::Output {
+        self.get(index).expect("Id not found in ComponentMap")
+    }
+}
+
+impl<ID, T> IndexMut<&Id<ID>> for ComponentMap<Id<ID>, T> {
+    fn index_mut(&mut self, index: &Id<ID>) -> &mut Self::Output {
+        self.get_mut(index).expect("Id not found in ComponentMap")
+    }
+}
+
+impl<ID, T> Index<Valid<'_, ID>> for ComponentMap<GenId<ID>, T> {
+    type Output = T;
+
+    fn index(&self, index: Valid<'_, ID>) -> &Self::Output {
+        self.get(index).expect("Id not found in ComponentMap")
+    }
+}
+
+impl<ID, T> IndexMut<Valid<'_, ID>> for ComponentMap<GenId<ID>, T> {
+    fn index_mut(&mut self, index: Valid<'_, ID>) -> &mut Self::Output {
+        self.get_mut(index).expect("Id not found in ComponentMap")
+    }
+}
+
+impl<ID, T> Index<&Valid<'_, ID>> for ComponentMap<GenId<ID>, T> {
+    type Output = T;
+
+    fn index(&self, index: &Valid<'_, ID>) -> &Self::Output {
+        self.get(index).expect("Id not found in ComponentMap")
+    }
+}
+
+impl<ID, T> IndexMut<&Valid<'_, ID>> for ComponentMap<GenId<ID>, T> {
+    fn index_mut(&mut self, index: &Valid<'_, ID>) -> &mut Self::Output {
+        self.get_mut(index).expect("Id not found in ComponentMap")
+    }
+}
+
 impl<ID, T> Insert<Id<ID>, T> for ComponentMap<Id<ID>, T> {
     #[inline(always)]
     fn insert(&mut self, id: &Id<ID>, value: T) {
@@ -87,4 +290,47 @@ impl<ID, T> Insert<Valid<'_, ID>, T> for ComponentMap<GenId<ID>, T> {
     fn insert(&mut self, id: &Valid<ID>, value: T) {
         self.values.insert(id.id, value);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_api() {
+        let mut alloc = GenAllocator::<()>::default();
+        let mut mass = Component::<(), u32>::default();
+        let mut cargo = ComponentMap::<GenId<()>, u32>::default();
+
+        let ids: Vec<_> = (0..4)
+            .map(|i| {
+                let id = alloc.create();
+                mass.insert(&id, i);
+                if i % 2 == 0 {
+                    cargo.insert(&id, 10);
+                }
+                id.id()
+            })
+            .collect();
+
+        assert_eq!(2, cargo.len());
+        assert!(cargo.contains(&ids[2]));
+        assert!(!cargo.contains(&ids[1]));
+
+        *cargo.entry(ids[1]).or_default() += 5;
+        cargo[&alloc.validate(ids[2]).unwrap()] += 1;
+        assert_eq!(Some(&11), cargo.get_opt(&alloc.validate(ids[2]).unwrap()));
+
+        alloc.kill(ids[0]);
+
+        let mut joined: Vec<_> = cargo.join(&mass, &alloc).map(|(_, c, m)| c + m).collect();
+        joined.sort();
+        assert_eq!(vec![6, 13], joined);
+
+        cargo.retain(|id, _| alloc.is_alive(*id));
+        assert_eq!(16, cargo.par_iter().map(|(_, c)| *c).sum::<u32>());
+
+        assert_eq!(2, cargo.drain().count());
+        assert!(cargo.is_empty());
+    }
+}
@@ -2,11 +2,17 @@ mod allocators;
 mod components;
 mod maps;
 mod ids;
+mod queries;
+mod tags;
 
 pub use allocators::*;
 pub use components::*;
 pub use maps::*;
 pub use ids::*;
+pub use queries::*;
+pub use tags::*;
+
+use std::fmt::{Display, Formatter};
 
 pub trait Insert<ID, T> {
     fn insert(&mut self, id: &ID, value: T);
@@ -22,6 +28,52 @@ pub trait GetOpt<ID, T> {
     fn get_opt_mut(&mut self, id: ID) -> Option<&mut T>;
 }
 
+pub trait Tag<ID> {
+    fn set(&mut self, id: ID);
+    fn unset(&mut self, id: ID);
+    fn contains(&self, id: ID) -> bool;
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct DeleteRestricted {
+    pub arena: &'static str,
+    pub field: &'static str,
+}
+
+impl DeleteRestricted {
+    pub fn new(arena: &'static str, field: &'static str) -> Self {
+        Self { arena, field }
+    }
+}
+
+impl Display for DeleteRestricted {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Deletion restricted by reference: {}.{}", self.arena, self.field)
+    }
+}
+
+impl std::error::Error for DeleteRestricted {}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct LinkCycle {
+    pub arena: &'static str,
+    pub field: &'static str,
+}
+
+impl LinkCycle {
+    pub fn new(arena: &'static str, field: &'static str) -> Self {
+        Self { arena, field }
+    }
+}
+
+impl Display for LinkCycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Link would create a cycle: {}.{}", self.arena, self.field)
+    }
+}
+
+impl std::error::Error for LinkCycle {}
+
 pub trait GetTuple2<ID, T1, T2> {
     fn get(&self, id: ID) -> Option<(&T1, &T2)>;
 }
@@ -0,0 +1,306 @@
+use super::{Component, ComponentMap, FixedAllocator, GenAllocator, Get, GetOpt, Id, Tag, TagComponent, Valid};
+use rayon::iter::*;
+use std::hash::Hash;
+
+/// A term of a join query, fetched for one id at a time.
+/// Returning `None` filters the id out of the query.
+pub trait Query<ID, T> {
+    fn fetch(&self, id: ID) -> Option<T>;
+}
+
+// required: &component
+impl<'a, ID, T: 'a, A: Get<ID, T>> Query<ID, &'a T> for &'a A {
+    #[inline(always)]
+    fn fetch(&self, id: ID) -> Option<&'a T> {
+        Get::get(*self, id)
+    }
+}
+
+/// Optional term, yields `None` without filtering the id out.
+pub struct Opt<'a, A>(pub &'a A);
+
+impl<'a, ID, K, T: 'a> Query<ID, Option<&'a T>> for Opt<'a, Component<K, Option<T>>>
+where
+    Component<K, Option<T>>: GetOpt<ID, T>,
+{
+    #[inline(always)]
+    fn fetch(&self, id: ID) -> Option<Option<&'a T>> {
+        Some(self.0.get_opt(id))
+    }
+}
+
+impl<'a, ID, K: Hash + Eq, T: 'a> Query<ID, Option<&'a T>> for Opt<'a, ComponentMap<K, T>>
+where
+    ComponentMap<K, T>: Get<ID, T>,
+{
+    #[inline(always)]
+    fn fetch(&self, id: ID) -> Option<Option<&'a T>> {
+        Some(self.0.get(id))
+    }
+}
+
+/// Required term over a sparse component, filters out ids without a value.
+pub struct Req<'a, A>(pub &'a A);
+
+impl<'a, ID, K, T: 'a> Query<ID, &'a T> for Req<'a, Component<K, Option<T>>>
+where
+    Component<K, Option<T>>: GetOpt<ID, T>,
+{
+    #[inline(always)]
+    fn fetch(&self, id: ID) -> Option<&'a T> {
+        self.0.get_opt(id)
+    }
+}
+
+/// Excluded term, filters out ids that have a value.
+pub struct Without<'a, A>(pub &'a A);
+
+impl<'a, ID, K, T> Query<ID, ()> for Without<'a, Component<K, Option<T>>>
+where
+    Component<K, Option<T>>: GetOpt<ID, T>,
+{
+    #[inline(always)]
+    fn fetch(&self, id: ID) -> Option<()> {
+        match self.0.get_opt(id) {
+            Some(_) => None,
+            None => Some(()),
+        }
+    }
+}
+
+impl<'a, ID, K: Hash + Eq, T> Query<ID, ()> for Without<'a, ComponentMap<K, T>>
+where
+    ComponentMap<K, T>: Get<ID, T>,
+{
+    #[inline(always)]
+    fn fetch(&self, id: ID) -> Option<()> {
+        match self.0.get(id) {
+            Some(_) => None,
+            None => Some(()),
+        }
+    }
+}
+
+// tags only filter, they carry no value
+impl<ID, K> Query<ID, ()> for &TagComponent<K>
+where
+    TagComponent<K>: Tag<ID>,
+{
+    #[inline(always)]
+    fn fetch(&self, id: ID) -> Option<()> {
+        if self.contains(id) {
+            Some(())
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, ID, K> Query<ID, ()> for Without<'a, TagComponent<K>>
+where
+    TagComponent<K>: Tag<ID>,
+{
+    #[inline(always)]
+    fn fetch(&self, id: ID) -> Option<()> {
+        if self.0.contains(id) {
+            None
+        } else {
+            Some(())
+        }
+    }
+}
+
+macro_rules! tuple_query {
+    ($(($q:ident, $t:ident, $i:tt)),+) => {
+        impl<ID: Copy, $($q: Query<ID, $t>, $t),+> Query<ID, ($($t,)+)> for ($($q,)+) {
+            #[inline(always)]
+            fn fetch(&self, id: ID) -> Option<($($t,)+)> {
+                Some(($(self.$i.fetch(id)?,)+))
+            }
+        }
+    };
+}
+
+tuple_query!((A, TA, 0));
+tuple_query!((A, TA, 0), (B, TB, 1));
+tuple_query!((A, TA, 0), (B, TB, 1), (C, TC, 2));
+tuple_query!((A, TA, 0), (B, TB, 1), (C, TC, 2), (D, TD, 3));
+tuple_query!((A, TA, 0), (B, TB, 1), (C, TC, 2), (D, TD, 3), (E, TE, 4));
+tuple_query!((A, TA, 0), (B, TB, 1), (C, TC, 2), (D, TD, 3), (E, TE, 4), (F, TF, 5));
+
+impl<ID> FixedAllocator<ID> {
+    pub fn query<'a, Q, T>(&'a self, query: Q) -> impl Iterator<Item = (Id<ID>, T)> + 'a
+    where
+        Q: Query<Id<ID>, T> + 'a,
+    {
+        self.ids()
+            .filter_map(move |id| query.fetch(id).map(|item| (id, item)))
+    }
+
+    pub fn query_mut<'a, C, Q, T>(
+        &'a self,
+        component: &'a mut Component<ID, C>,
+        query: Q,
+    ) -> impl Iterator<Item = (Id<ID>, &'a mut C, T)> + 'a
+    where
+        Q: Query<Id<ID>, T> + 'a,
+    {
+        self.ids()
+            .zip(component.iter_mut())
+            .filter_map(move |(id, value)| query.fetch(id).map(|item| (id, value, item)))
+    }
+}
+
+impl<ID: Send + Sync> FixedAllocator<ID> {
+    pub fn par_query<'a, Q, T: Send>(&'a self, query: Q) -> impl ParallelIterator<Item = (Id<ID>, T)> + 'a
+    where
+        Q: Query<Id<ID>, T> + Send + Sync + 'a,
+    {
+        (0..self.len())
+            .into_par_iter()
+            .map(Id::new)
+            .filter_map(move |id| query.fetch(id).map(|item| (id, item)))
+    }
+
+    pub fn par_query_mut<'a, C: Send + Sync, Q, T: Send>(
+        &'a self,
+        component: &'a mut Component<ID, C>,
+        query: Q,
+    ) -> impl ParallelIterator<Item = (Id<ID>, &'a mut C, T)> + 'a
+    where
+        Q: Query<Id<ID>, T> + Send + Sync + 'a,
+    {
+        component
+            .par_iter_mut()
+            .enumerate()
+            .filter_map(move |(i, value)| {
+                let id = Id::new(i);
+                query.fetch(id).map(|item| (id, value, item))
+            })
+    }
+}
+
+impl<ID> GenAllocator<ID> {
+    pub fn query<'a, Q, T>(&'a self, query: Q) -> impl Iterator<Item = (Valid<'a, ID>, T)> + 'a
+    where
+        Q: for<'b> Query<&'b Valid<'a, ID>, T> + 'a,
+    {
+        self.ids()
+            .filter_map(move |id| query.fetch(&id).map(|item| (id, item)))
+    }
+
+    pub fn query_mut<'a, C, Q, T>(
+        &'a self,
+        component: &'a mut Component<ID, C>,
+        query: Q,
+    ) -> impl Iterator<Item = (Valid<'a, ID>, &'a mut C, T)> + 'a
+    where
+        Q: for<'b> Query<&'b Valid<'a, ID>, T> + 'a,
+    {
+        component
+            .iter_living_mut(self)
+            .filter_map(move |(id, value)| query.fetch(&id).map(|item| (id, value, item)))
+    }
+}
+
+impl<ID: Send + Sync> GenAllocator<ID> {
+    pub fn par_query<'a, Q, T: Send>(&'a self, query: Q) -> impl ParallelIterator<Item = (Valid<'a, ID>, T)> + 'a
+    where
+        Q: for<'b> Query<&'b Valid<'a, ID>, T> + Send + Sync + 'a,
+    {
+        (0..self.len())
+            .into_par_iter()
+            .filter_map(move |i| self.validate_index(i))
+            .filter_map(move |id| query.fetch(&id).map(|item| (id, item)))
+    }
+
+    pub fn par_query_mut<'a, C: Send + Sync, Q, T: Send>(
+        &'a self,
+        component: &'a mut Component<ID, C>,
+        query: Q,
+    ) -> impl ParallelIterator<Item = (Valid<'a, ID>, &'a mut C, T)> + 'a
+    where
+        Q: for<'b> Query<&'b Valid<'a, ID>, T> + Send + Sync + 'a,
+    {
+        component
+            .par_iter_living_mut(self)
+            .filter_map(move |(id, value)| query.fetch(&id).map(|item| (id, value, item)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::Insert;
+
+    #[test]
+    fn join() {
+        let mut alloc = GenAllocator::<()>::default();
+        let mut speed = Component::<(), u32>::default();
+        let mut name = Component::<(), Option<&str>>::default();
+        let mut cargo = ComponentMap::<crate::ecs::GenId<()>, u32>::default();
+
+        let ids: Vec<_> = (0..4)
+            .map(|i| {
+                let id = alloc.create();
+                speed.insert(&id, i);
+                name.insert(&id, if i % 2 == 0 { Some("even") } else { None });
+                if i > 1 {
+                    cargo.insert(&id, 10 * i);
+                }
+                id.id()
+            })
+            .collect();
+
+        alloc.kill(ids[3]);
+
+        let named: Vec<_> = alloc
+            .query((&speed, Req(&name), Opt(&cargo)))
+            .map(|(_, (s, n, c))| (*s, *n, c.copied()))
+            .collect();
+        assert_eq!(vec![(0, "even", None), (2, "even", Some(20))], named);
+
+        let unnamed: Vec<_> = alloc
+            .query((&speed, Without(&name)))
+            .map(|(id, _)| id.id())
+            .collect();
+        assert_eq!(vec![ids[1]], unnamed);
+
+        let total: u32 = alloc.par_query((&speed, &cargo)).map(|(_, (s, c))| s + c).sum();
+        assert_eq!(22, total);
+
+        alloc
+            .par_query_mut(&mut speed, (Opt(&cargo),))
+            .for_each(|(_, s, (c,))| *s += c.copied().unwrap_or(100));
+        assert_eq!(vec![100, 101, 22, 3], speed.iter().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn fixed_join() {
+        let mut alloc = FixedAllocator::<()>::default();
+        let mut mass = Component::<(), u32>::default();
+        let mut moon = ComponentMap::<Id<()>, u32>::default();
+
+        for i in 0..3 {
+            let id = alloc.create();
+            mass.insert(&id, i);
+            if i == 1 {
+                moon.insert(&id, 7);
+            }
+        }
+
+        let with_moon: Vec<_> = alloc.query((&mass, &moon)).map(|(_, (m, c))| m + c).collect();
+        assert_eq!(vec![8], with_moon);
+
+        let count = alloc.par_query((&mass, Without(&moon))).count();
+        assert_eq!(2, count);
+
+        alloc
+            .query_mut(&mut mass, (Opt(&moon),))
+            .for_each(|(_, m, (c,))| *m += c.copied().unwrap_or_default());
+        alloc
+            .par_query_mut(&mut mass, (&moon,))
+            .for_each(|(_, m, (c,))| *m += c);
+        assert_eq!(vec![0, 15, 2], mass.iter().copied().collect::<Vec<_>>());
+    }
+}
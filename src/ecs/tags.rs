@@ -0,0 +1,164 @@
+use super::{GenAllocator, GenId, Id, Tag, Valid};
+use bit_set::BitSet;
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone)]
+pub struct TagComponent<ID> {
+    values: BitSet,
+    marker: PhantomData<ID>,
+}
+
+impl<ID> Default for TagComponent<ID> {
+    fn default() -> Self {
+        Self {
+            values: BitSet::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+// permanent rows are never killed, so every set bit belongs to a row
+impl<ID> TagComponent<Id<ID>> {
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Id<ID>> + '_ {
+        self.values.iter().map(Id::new)
+    }
+}
+
+// a bit may outlive its id if the row was killed without being removed, so tags are read through the allocator
+impl<ID> TagComponent<GenId<ID>> {
+    // skips the slots of killed ids
+    pub fn iter_living<'a>(&'a self, alloc: &'a GenAllocator<ID>) -> impl Iterator<Item = Valid<'a, ID>> + 'a {
+        self.values
+            .iter()
+            .filter_map(move |index| alloc.validate_index(index))
+    }
+}
+
+impl<ID> Tag<Id<ID>> for TagComponent<Id<ID>> {
+    #[inline(always)]
+    fn set(&mut self, id: Id<ID>) {
+        self.values.insert(id.index);
+    }
+
+    #[inline(always)]
+    fn unset(&mut self, id: Id<ID>) {
+        self.values.remove(id.index);
+    }
+
+    #[inline(always)]
+    fn contains(&self, id: Id<ID>) -> bool {
+        self.values.contains(id.index)
+    }
+}
+
+impl<ID> Tag<&Id<ID>> for TagComponent<Id<ID>> {
+    #[inline(always)]
+    fn set(&mut self, id: &Id<ID>) {
+        self.values.insert(id.index);
+    }
+
+    #[inline(always)]
+    fn unset(&mut self, id: &Id<ID>) {
+        self.values.remove(id.index);
+    }
+
+    #[inline(always)]
+    fn contains(&self, id: &Id<ID>) -> bool {
+        self.values.contains(id.index)
+    }
+}
+
+impl<ID> Tag<Valid<'_, ID>> for TagComponent<GenId<ID>> {
+    #[inline(always)]
+    fn set(&mut self, id: Valid<ID>) {
+        self.values.insert(id.id.index);
+    }
+
+    #[inline(always)]
+    fn unset(&mut self, id: Valid<ID>) {
+        self.values.remove(id.id.index);
+    }
+
+    #[inline(always)]
+    fn contains(&self, id: Valid<ID>) -> bool {
+        self.values.contains(id.id.index)
+    }
+}
+
+impl<ID> Tag<&Valid<'_, ID>> for TagComponent<GenId<ID>> {
+    #[inline(always)]
+    fn set(&mut self, id: &Valid<ID>) {
+        self.values.insert(id.id.index);
+    }
+
+    #[inline(always)]
+    fn unset(&mut self, id: &Valid<ID>) {
+        self.values.remove(id.id.index);
+    }
+
+    #[inline(always)]
+    fn contains(&self, id: &Valid<ID>) -> bool {
+        self.values.contains(id.id.index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::{Component, FixedAllocator, Insert, Without};
+
+    #[test]
+    fn tags() {
+        let mut alloc = GenAllocator::<()>::default();
+        let mut mass = Component::<(), u32>::default();
+        let mut capital = TagComponent::<GenId<()>>::default();
+
+        let ids: Vec<_> = (0..4)
+            .map(|i| {
+                let id = alloc.create();
+                mass.insert(&id, i);
+                if i % 2 == 0 {
+                    capital.set(&id);
+                }
+                id.id()
+            })
+            .collect();
+
+        alloc.kill(ids[2]);
+
+        assert_eq!(vec![ids[0]], capital.iter_living(&alloc).map(|id| id.id()).collect::<Vec<_>>());
+
+        let tagged: Vec<_> = alloc.query((&mass, &capital)).map(|(_, (m, _))| *m).collect();
+        assert_eq!(vec![0], tagged);
+
+        let untagged: Vec<_> = alloc.query((&mass, Without(&capital))).map(|(_, (m, _))| *m).collect();
+        assert_eq!(vec![1, 3], untagged);
+
+        capital.unset(&alloc.validate(ids[0]).unwrap());
+        assert!(!capital.contains(&alloc.validate(ids[0]).unwrap()));
+    }
+
+    #[test]
+    fn fixed_tags() {
+        let mut alloc = FixedAllocator::<()>::default();
+        let mut capital = TagComponent::<Id<()>>::default();
+
+        let ids: Vec<_> = (0..3).map(|_| alloc.create()).collect();
+        capital.set(ids[0]);
+        capital.set(&ids[2]);
+
+        assert_eq!(2, capital.len());
+        assert_eq!(vec![ids[0], ids[2]], capital.iter().collect::<Vec<_>>());
+        assert_eq!(vec![ids[1]], alloc.query(Without(&capital)).map(|(id, _)| id).collect::<Vec<_>>());
+    }
+}
@@ -3,6 +3,7 @@ use crate::lifespans::*;
 use code_gen::*;
 use std::marker::PhantomData;
 use crate::worlds::World;
+use crate::errors::SchemaError;
 use code_gen::Visibility::Pub;
 
 //	From	    To	        Relationsh	Use Case	                                        Example
@@ -16,23 +17,47 @@ use code_gen::Visibility::Pub;
 //	Transient	Transient	ManyOwns	A -> [B]	                                        NEW
 //	Permanent	Permanent	Ref     	A -- B	                                            all bodies reference a system
 //	Permanent	Permanent	MaybeRef	A -- Opt<B>	                                        ??
-//	Permanent	Permanent	ManyRef	    A -- [B]	                                        NEW
+//	Permanent	Permanent	ManyRef	    A -- [B]	                                        add_many_reference
 //	Permanent	Transient	Ref	        INVALID, cannot be unlinked if child removed	    -
 //	Permanent	Transient	MaybeRef	A -- Opt<B>	                                        ??
 //	Permanent	Transient	ManyRef 	A -- [B]	                                        Systems lists bodies contained
 //	Transient	Permanent	Ref	        A -- B	                                            colony references the body it's built upon
 //	Transient	Permanent	MaybeRef	A -- Opt<B>	                                        ships can reference a system, but may not be in one
-//	Transient	Permanent	ManyRef	    A -- [B]	                                        NEW
+//	Transient	Permanent	ManyRef	    A -- [B]	                                        add_many_reference
 //	Transient	Transient	Ref	        MAYBE INVALID, must point at owner so that it can be deleted with it
 //	Transient	Transient	MaybeRef	A -- Opt<B>                                         ship refers to its controller
-//	Transient	Transient	ManyRef	    A -- [B]                                            NEW
+//	Transient	Transient	ManyRef	    A -- [B]                                            add_many_reference
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Relationship {
+    MaybeOwns,
+    ManyOwns,
+    Ref,
+    // a required reference that cascades, the referrer is deleted along with its owner
+    OwnerRef,
+    MaybeRef,
+    ManyRef,
+}
+
+/// Why the matrix above rules out a relationship from one arena to another, if it does.
+pub fn get_invalid_relationship(from_deletable: bool, to_deletable: bool, relationship: Relationship) -> Option<&'static str> {
+    use Relationship::*;
+
+    match (from_deletable, to_deletable, relationship) {
+        (true, false, MaybeOwns) | (true, false, ManyOwns) => Some("child entity will leak if parent removed"),
+        (false, true, Ref) => Some("cannot be unlinked if child removed"),
+        (true, true, Ref) => Some("must point at owner so that it can be deleted with it"),
+        (false, _, OwnerRef) | (_, false, OwnerRef) => Some("only transient arenas can be deleted with a transient owner"),
+        _ => None,
+    }
+}
 
 #[derive(Debug)]
 pub struct EntityCore {
     pub base: ArenaName,
     pub children: Vec<ArenaName>,    // one to maybe one
     pub collections: Vec<ArenaName>, // one to many
-    pub enums: Vec<EntityEnum>,
+    pub enums: Vec<EntityEnumCore>,
 }
 
 impl EntityCore {
@@ -49,6 +74,73 @@ impl EntityCore {
         CamelCase::new(&format!("{}Entity", self.base))
     }
 
+    pub fn get_arenas(&self) -> impl Iterator<Item = &ArenaName> {
+        std::iter::once(&self.base)
+            .chain(self.children.iter())
+            .chain(self.collections.iter())
+            .chain(self.enums.iter().flat_map(|e| e.options.iter()))
+    }
+
+    // an arena can only fill one place in an entity, each one becomes a field of the base arena
+    fn check_arena<L: Lifespan, C: Lifespan>(&self, arena: &ArenaName, relationship: Relationship) -> std::result::Result<(), SchemaError> {
+        if self.owns_arena(arena) {
+            return Err(SchemaError::DuplicateEntityArena {
+                entity: self.base.to_string(),
+                arena: arena.to_string(),
+            });
+        }
+
+        match self.get_ownership_error(arena, L::is_deletable(), C::is_deletable(), relationship) {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn get_ownership_error(&self, child: &ArenaName, from_deletable: bool, to_deletable: bool, relationship: Relationship) -> Option<SchemaError> {
+        get_invalid_relationship(from_deletable, to_deletable, relationship).map(|reason| SchemaError::InvalidOwnership {
+            entity: self.base.to_string(),
+            arena: child.to_string(),
+            reason: reason.to_string(),
+        })
+    }
+
+    // the relationship of each owned arena, an enum option may be missing so it is owned like a child
+    pub(crate) fn get_owned_relationships(&self) -> impl Iterator<Item = (&ArenaName, Relationship)> {
+        self.children
+            .iter()
+            .map(|c| (c, Relationship::MaybeOwns))
+            .chain(self.collections.iter().map(|c| (c, Relationship::ManyOwns)))
+            .chain(self.enums.iter().flat_map(|e| e.options.iter()).map(|c| (c, Relationship::MaybeOwns)))
+    }
+
+    fn push_child<L: Lifespan, C: Lifespan>(&mut self, child: &Arena<C>) -> std::result::Result<(), SchemaError> {
+        self.check_arena::<L, C>(&child.name(), Relationship::MaybeOwns)?;
+        self.children.push(child.name());
+        Ok(())
+    }
+
+    fn push_collection<L: Lifespan, C: Lifespan>(&mut self, child: &Arena<C>) -> std::result::Result<(), SchemaError> {
+        self.check_arena::<L, C>(&child.name(), Relationship::ManyOwns)?;
+        self.collections.push(child.name());
+        Ok(())
+    }
+
+    fn push_enum<L: Lifespan, C: Lifespan>(&mut self, entity_enum: EntityEnumCore) -> std::result::Result<(), SchemaError> {
+        for (i, option) in entity_enum.options.iter().enumerate() {
+            self.check_arena::<L, C>(option, Relationship::MaybeOwns)?;
+
+            if entity_enum.options[..i].contains(option) {
+                return Err(SchemaError::DuplicateEntityArena {
+                    entity: self.base.to_string(),
+                    arena: option.to_string(),
+                });
+            }
+        }
+
+        self.enums.push(entity_enum);
+        Ok(())
+    }
+
     pub(crate) fn owns_arena(&self, arena: &ArenaName) -> bool {
         return self.base == *arena
             || self.children.contains(&arena)
@@ -70,6 +162,13 @@ impl EntityCore {
         });
         fields.extend(child_fields);
 
+        let collection_fields = self.collections.iter().map(|c| Field {
+            visibility: Pub,
+            name: c.as_field_name(),
+            field_type: Type::new(&format!("Vec<{}>", c.get_row_type())),
+        });
+        fields.extend(collection_fields);
+
         let enum_fields = self.enums.iter().map(|e| Field {
             visibility: Default::default(),
             name: e.name.into_snake_case(),
@@ -98,25 +197,39 @@ impl<L: Lifespan> Entity<L> {
     }
 
     pub fn get_arenas(&self) -> impl Iterator<Item = &ArenaName> {
-        std::iter::once(&self.entity.base)
-            .chain(self.entity.children.iter())
-            .chain(self.entity.collections.iter())
-    }
-
-    pub fn add_enum(&mut self, entity_enum: EntityEnum) {
-        self.entity.enums.push(entity_enum);
+        self.entity.get_arenas()
     }
 }
 
 impl Entity<Permanent> {
     // 1 to Option
     pub fn add_child(&mut self, child: &Arena<impl Lifespan>) {
-        self.entity.children.push(child.name());
+        self.try_add_child(child)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_child<C: Lifespan>(&mut self, child: &Arena<C>) -> std::result::Result<(), SchemaError> {
+        self.entity.push_child::<Permanent, C>(child)
     }
 
     // 1 to [0..]
     pub fn add_collection(&mut self, child: &Arena<impl Lifespan>) {
-        self.entity.collections.push(child.name());
+        self.try_add_collection(child)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_collection<C: Lifespan>(&mut self, child: &Arena<C>) -> std::result::Result<(), SchemaError> {
+        self.entity.push_collection::<Permanent, C>(child)
+    }
+
+    // 1 to one of [A, B, ..]
+    pub fn add_enum(&mut self, entity_enum: EntityEnum<impl Lifespan>) {
+        self.try_add_enum(entity_enum)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_enum<C: Lifespan>(&mut self, entity_enum: EntityEnum<C>) -> std::result::Result<(), SchemaError> {
+        self.entity.push_enum::<Permanent, C>(entity_enum.entity_enum)
     }
 }
 
@@ -124,38 +237,74 @@ impl Entity<Permanent> {
 impl Entity<Transient> {
     // 1 to Option
     pub fn add_child(&mut self, child: &Arena<Transient>) {
-        self.entity.children.push(child.name());
+        self.try_add_child(child)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_child(&mut self, child: &Arena<Transient>) -> std::result::Result<(), SchemaError> {
+        self.entity.push_child::<Transient, Transient>(child)
     }
 
     // 1 to [0..]
     pub fn add_collection(&mut self, child: &Arena<Transient>) {
-        self.entity.collections.push(child.name());
+        self.try_add_collection(child)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_collection(&mut self, child: &Arena<Transient>) -> std::result::Result<(), SchemaError> {
+        self.entity.push_collection::<Transient, Transient>(child)
+    }
+
+    // 1 to one of [A, B, ..]
+    pub fn add_enum(&mut self, entity_enum: EntityEnum<Transient>) {
+        self.try_add_enum(entity_enum)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_add_enum(&mut self, entity_enum: EntityEnum<Transient>) -> std::result::Result<(), SchemaError> {
+        self.entity.push_enum::<Transient, Transient>(entity_enum.entity_enum)
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct EntityEnum {
+pub struct EntityEnumCore {
     pub name: CamelCase,
     pub options: Vec<ArenaName>
 }
 
-// TODO impl From<VALID_ID> and From<Row> for entity enums
-// TODO add function World::create_ENTITY_ENUM_ROW
-// TODO for create_ENTITY, create and link to ENTITY_ENUM_ID
+#[derive(Debug, Clone)]
+pub struct EntityEnum<L: Lifespan> {
+    pub entity_enum: EntityEnumCore,
+    marker: PhantomData<L>,
+}
+
+impl<L: Lifespan> EntityEnum<L> {
+    pub fn new(enum_type: &str, options: Vec<&Arena<L>>) -> Self {
+        Self::try_new(enum_type, options)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_new(enum_type: &str, options: Vec<&Arena<L>>) -> std::result::Result<Self, SchemaError> {
+        let name = enum_type.parse().map_err(|_| SchemaError::InvalidEnumName {
+            name: enum_type.to_string(),
+        })?;
 
-impl EntityEnum {
-    pub fn new(enum_type: &str, options: Vec<&Arena<Transient>>) -> Self {
         let options = options
             .into_iter()
             .map(|a| a.name())
             .collect();
 
-        Self {
-            name: CamelCase::new(enum_type),
-            options
-        }
+        Ok(Self {
+            entity_enum: EntityEnumCore {
+                name,
+                options
+            },
+            marker: PhantomData,
+        })
     }
+}
 
+impl EntityEnumCore {
     pub fn get_row_type(&self) -> Type {
         Type::new(&format!("{}Row", self.name))
     }
@@ -171,11 +320,17 @@ impl EntityEnum {
                 let arena_row = world.generate_arena_row(&world.get_arena(o));
                 row_enum.add_option(EnumOption::new(o.as_str(), vec![&arena_row.typ.to_string()]))
             });
-        
+
+        let enum_traits = self
+            .options
+            .iter()
+            .map(|opt| Self::from_impl(&typ, &opt.get_row_type(), opt))
+            .collect();
+
         EnumType {
             base,
             enum_impl: None,
-            enum_traits: vec![]
+            enum_traits
         }
     }
 
@@ -190,20 +345,29 @@ impl EntityEnum {
                 id_enum.add_option(EnumOption::new(o.as_str(), vec![&arena_id.to_string()]))
             });
 
-        let enum_traits: Vec<TraitImpl> = self
+        let id_traits = self
             .options
             .iter()
+            .map(|opt| Self::from_impl(&typ, &world.get_id(opt), opt));
+
+        let valid_traits = self
+            .options
+            .iter()
+            .filter(|opt| world.get_valid_id(opt).name.as_str() == "Valid")
             .map(|opt| {
-                let id = world.get_id(opt);
+                let mut valid = world.get_valid_id(opt);
+                valid.types.push_front("'_");
+
                 Self::from_trait()
                     .impl_for(&typ)
-                    .with_generics(Generics::one(&id.to_string()))
+                    .with_generics(Generics::one(&valid.to_string()))
                     .add_function(
                         Self::from_trait_function()
-                            .with_parameters(&format!("value: {}", id))
-                            .add_line(CodeLine::new(0, &format!("{}::{}(value)", typ, opt))))
-            })
-            .collect();
+                            .with_parameters(&format!("value: {}", valid))
+                            .add_line(CodeLine::new(0, &format!("{}::{}(value.id())", typ, opt))))
+            });
+
+        let enum_traits: Vec<TraitImpl> = id_traits.chain(valid_traits).collect();
 
         EnumType {
             base,
@@ -212,6 +376,16 @@ impl EntityEnum {
         }
     }
 
+    fn from_impl(typ: &Type, value: &Type, opt: &ArenaName) -> TraitImpl {
+        Self::from_trait()
+            .impl_for(typ)
+            .with_generics(Generics::one(&value.to_string()))
+            .add_function(
+                Self::from_trait_function()
+                    .with_parameters(&format!("value: {}", value))
+                    .add_line(CodeLine::new(0, &format!("{}::{}(value)", typ, opt))))
+    }
+
     fn from_trait() -> Trait {
         Trait::new("From")
             .with_generics(Generics::one("T"))
@@ -0,0 +1,170 @@
+use std::fmt::*;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SchemaError {
+    InvalidArenaName { name: String },
+    InvalidFieldName { arena: String, field: String },
+    InvalidTypeField { arena: String, type_name: String },
+    DuplicateArena { arena: String },
+    DuplicateEntity { entity: String },
+    MissingEntityArena { entity: String, arena: String },
+    MissingReferenceTarget { arena: String, field: String, target: String },
+    ReferenceNotFound { arena: String, field: String },
+    ComponentNotFound { arena: String, field: String },
+    NullifyRequiredReference { arena: String, field: String },
+    PermanentCascade { arena: String, field: String },
+    OwnedCascade { arena: String, field: String },
+    InvalidReference { arena: String, field: String, target: String, reason: String },
+    InvalidOwnership { entity: String, arena: String, reason: String },
+    FieldCollision { arena: String, field: String, origins: Vec<FieldOrigin> },
+    InvalidDerivedInput { arena: String, field: String, input: String },
+    DerivedCycle { arena: String },
+    InvalidEnumName { name: String },
+    DuplicateReference { arena: String, field: String },
+    DuplicateEntityArena { entity: String, arena: String },
+    UnknownArena { arena: String },
+}
+
+/// Where a generated arena struct field comes from.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum FieldOrigin {
+    Component,
+    Reference { target: String },
+    LateReference { target: String },
+    ManyReference { target: String },
+    Tag,
+    EntityEnum { name: String },
+    Child { arena: String },
+    Collection { arena: String },
+    Owner { entity: String },
+    ReverseIndex { referrer: String, field: String },
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            SchemaError::InvalidArenaName { name } => {
+                write!(f, "Arena names must be in CamelCase: {}", name)
+            }
+            SchemaError::InvalidFieldName { arena, field } => {
+                write!(f, "Fields must be in snake_case: {}.{}", arena, field)
+            }
+            SchemaError::InvalidTypeField { arena, type_name } => write!(
+                f,
+                "Given type cannot be formatted as snake_case: {}.{}",
+                arena, type_name
+            ),
+            SchemaError::DuplicateArena { arena } => write!(f, "Duplicate arena name: {}", arena),
+            SchemaError::DuplicateEntity { entity } => {
+                write!(f, "Duplicate entity for arena: {}", entity)
+            }
+            SchemaError::MissingEntityArena { entity, arena } => write!(
+                f,
+                "Arena must be inserted before Entity: {} (required by {})",
+                arena, entity
+            ),
+            SchemaError::MissingReferenceTarget {
+                arena,
+                field,
+                target,
+            } => write!(
+                f,
+                "Referenced arena must be inserted: {} (referenced by {}.{})",
+                target, arena, field
+            ),
+            SchemaError::ReferenceNotFound { arena, field } => {
+                write!(f, "Reference not found: {}.{}", arena, field)
+            }
+            SchemaError::ComponentNotFound { arena, field } => {
+                write!(f, "Component not found: {}.{}", arena, field)
+            }
+            SchemaError::NullifyRequiredReference { arena, field } => write!(
+                f,
+                "Required references cannot be nullified: {}.{}",
+                arena, field
+            ),
+            SchemaError::PermanentCascade { arena, field } => write!(
+                f,
+                "Permanent arenas cannot be deleted by cascade: {}.{}",
+                arena, field
+            ),
+            SchemaError::OwnedCascade { arena, field } => write!(
+                f,
+                "Owned arenas cannot be deleted by cascade: {}.{}",
+                arena, field
+            ),
+            SchemaError::InvalidReference {
+                arena,
+                field,
+                target,
+                reason,
+            } => write!(
+                f,
+                "Invalid reference from {}.{} to {}: {}",
+                arena, field, target, reason
+            ),
+            SchemaError::InvalidOwnership {
+                entity,
+                arena,
+                reason,
+            } => write!(f, "Invalid ownership of {} by {}: {}", arena, entity, reason),
+            SchemaError::FieldCollision {
+                arena,
+                field,
+                origins,
+            } => {
+                write!(f, "Duplicate field in arena: {}.{} (", arena, field)?;
+                for (i, origin) in origins.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", origin)?;
+                }
+                write!(f, ")")
+            }
+            SchemaError::InvalidDerivedInput { arena, field, input } => write!(
+                f,
+                "Derived component input must be a prior non-rare component or one behind a link: {}.{} (input of {})",
+                arena, input, field
+            ),
+            SchemaError::DerivedCycle { arena } => write!(
+                f,
+                "Derived components read each other through links: {}",
+                arena
+            ),
+            SchemaError::InvalidEnumName { name } => {
+                write!(f, "Entity enum names must be in CamelCase: {}", name)
+            }
+            SchemaError::DuplicateReference { arena, field } => {
+                write!(f, "Duplicate reference field: {}.{}", arena, field)
+            }
+            SchemaError::DuplicateEntityArena { entity, arena } => write!(
+                f,
+                "Arena is already part of the entity: {} (in {})",
+                arena, entity
+            ),
+            SchemaError::UnknownArena { arena } => write!(f, "Arena not found in World: {}", arena),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+impl Display for FieldOrigin {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            FieldOrigin::Component => write!(f, "component"),
+            FieldOrigin::Reference { target } => write!(f, "reference to {}", target),
+            FieldOrigin::LateReference { target } => write!(f, "late reference to {}", target),
+            FieldOrigin::ManyReference { target } => write!(f, "many reference to {}", target),
+            FieldOrigin::Tag => write!(f, "tag"),
+            FieldOrigin::EntityEnum { name } => write!(f, "entity enum {}", name),
+            FieldOrigin::Child { arena } => write!(f, "child link to {}", arena),
+            FieldOrigin::Collection { arena } => write!(f, "collection of {}", arena),
+            FieldOrigin::Owner { entity } => write!(f, "owner link to {}", entity),
+            FieldOrigin::ReverseIndex { referrer, field } => {
+                write!(f, "reverse index of {}.{}", referrer, field)
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::arenas::*;
-use crate::entities::{Entity, EntityCore};
+use crate::entities::{get_invalid_relationship, Entity, EntityCore, EntityEnumCore, Relationship};
+use crate::errors::{FieldOrigin, SchemaError};
 use crate::lifespans::*;
 use code_gen::Visibility::Pub;
 use code_gen::*;
@@ -7,7 +8,6 @@ use std::collections::{HashMap, HashSet};
 use std::fmt::*;
 use std::str::FromStr;
 
-// TODO EntityEnum: add state transition functions
 // TODO add delete function for transient entities
 
 #[derive(Debug, Default)]
@@ -18,6 +18,7 @@ pub struct World {
     pub arenas: Vec<ArenaCore>,
     pub entities: Vec<EntityCore>,
 
+    pub deletable_arenas: HashSet<ArenaName>,
     pub deletable_entities: HashSet<ArenaName>,
     pub allocator: HashMap<ArenaName, Type>,
     pub id: HashMap<ArenaName, Type>,
@@ -28,51 +29,63 @@ pub trait Insert<T> {
     fn insert(&mut self, value: T);
 }
 
-impl<L: Lifespan> Insert<Arena<L>> for World {
-    fn insert(&mut self, arena: Arena<L>) {
-        if self.contains_arena(&arena.arena.name) {
-            panic!(format!("Duplicate arena name: {}", arena.arena.name));
+/// Inserting always records the value, so that `World::validate` can report every problem at once.
+pub trait TryInsert<T> {
+    fn try_insert(&mut self, value: T) -> std::result::Result<(), Vec<SchemaError>>;
+}
+
+impl<T> Insert<T> for World
+where
+    World: TryInsert<T>,
+{
+    fn insert(&mut self, value: T) {
+        if let Err(errors) = self.try_insert(value) {
+            panic!("{}", errors[0])
         }
+    }
+}
 
-        self.allocator.insert(arena.name(), arena.allocator());
+impl TryInsert<Arena<Transient>> for World {
+    fn try_insert(&mut self, arena: Arena<Transient>) -> std::result::Result<(), Vec<SchemaError>> {
+        let errors: Vec<_> = self.get_reference_errors(&arena.arena, false).collect();
 
-        self.id.insert(arena.name(), arena.id_type());
+        self.deletable_arenas.insert(arena.name());
+        self.insert_arena(arena, errors)
+    }
+}
 
-        self.valid_id.insert(arena.name(), arena.valid_id_type());
+impl TryInsert<Arena<Permanent>> for World {
+    fn try_insert(&mut self, arena: Arena<Permanent>) -> std::result::Result<(), Vec<SchemaError>> {
+        let errors: Vec<_> = Self::get_permanent_cascade_errors(&arena.arena)
+            .chain(self.get_reference_errors(&arena.arena, true))
+            .collect();
 
-        self.arenas.push(arena.arena);
+        self.insert_arena(arena, errors)
     }
 }
 
-impl Insert<Entity<Transient>> for World {
-    fn insert(&mut self, entity: Entity<Transient>) {
-        if !entity.get_arenas().all(|a| self.contains_arena(a)) {
-            panic!(
-                "Arena must be inserted before Entity: {}",
-                entity.entity.base
-            );
-        }
-
+impl TryInsert<Entity<Transient>> for World {
+    fn try_insert(&mut self, entity: Entity<Transient>) -> std::result::Result<(), Vec<SchemaError>> {
         self.deletable_entities.insert(entity.entity.base.clone());
-        self.entities.push(entity.entity);
+        self.insert_entity(entity.entity)
     }
 }
 
-impl Insert<Entity<Permanent>> for World {
-    fn insert(&mut self, entity: Entity<Permanent>) {
-        if !entity.get_arenas().all(|a| self.contains_arena(a)) {
-            panic!(
-                "Arena must be inserted before Entity: {}",
-                entity.entity.base
-            );
-        }
-
-        self.entities.push(entity.entity);
+impl TryInsert<Entity<Permanent>> for World {
+    fn try_insert(&mut self, entity: Entity<Permanent>) -> std::result::Result<(), Vec<SchemaError>> {
+        self.insert_entity(entity.entity)
     }
 }
 
 impl Display for World {
     fn fmt(&self, f: &mut Formatter) -> Result {
+        if let Err(errors) = self.validate() {
+            for e in errors {
+                writeln!(f, "compile_error!({:?});", e.to_string())?;
+            }
+            return Ok(());
+        }
+
         for u in self.use_statements.iter() {
             writeln!(f, "{}", u).ok();
         }
@@ -93,6 +106,10 @@ impl Display for World {
             writeln!(f, "{}", row).ok();
         }
 
+        for view in self.generate_arena_row_views() {
+            writeln!(f, "{}", view).ok();
+        }
+
         for entity in self.generate_entities() {
             writeln!(f, "{}", entity).ok();
         }
@@ -109,11 +126,353 @@ impl Display for World {
     }
 }
 
+/// A row view struct, e.g. `BodyRef<'a>`. code_gen types cannot hold references, so the fields are written out here.
+#[derive(Debug, Clone)]
+pub struct RowView {
+    pub name: String,
+    pub mutable: bool,
+    pub fields: Vec<(SnakeCase, String)>,
+}
+
+impl RowView {
+    fn new<'a>(name: String, mutable: bool, components: impl IntoIterator<Item = &'a Component>) -> Self {
+        let fields = components
+            .into_iter()
+            .map(|c| (c.field_name.clone(), c.get_view_type(mutable)))
+            .collect();
+
+        Self { name, mutable, fields }
+    }
+}
+
+impl Display for RowView {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if !self.mutable {
+            writeln!(f, "#[derive(Debug, Clone)]")?;
+        }
+        writeln!(f, "pub struct {}<'a> {{", self.name)?;
+        for (name, view_type) in self.fields.iter() {
+            writeln!(f, "    pub {}: {},", name, view_type)?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
 impl World {
     pub fn new() -> Self {
         Default::default()
     }
 
+    fn insert_arena<L: Lifespan>(&mut self, arena: Arena<L>, mut errors: Vec<SchemaError>) -> std::result::Result<(), Vec<SchemaError>> {
+        if self.contains_arena(&arena.arena.name) {
+            errors.insert(0, SchemaError::DuplicateArena {
+                arena: arena.arena.name.to_string(),
+            });
+        }
+
+        self.allocator.insert(arena.name(), arena.allocator());
+
+        self.id.insert(arena.name(), arena.id_type());
+
+        self.valid_id.insert(arena.name(), arena.valid_id_type());
+
+        self.arenas.push(arena.arena);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn insert_entity(&mut self, entity: EntityCore) -> std::result::Result<(), Vec<SchemaError>> {
+        let mut errors = self.get_entity_errors(&entity);
+
+        if self.get_entity(&entity.base).is_some() {
+            errors.insert(0, SchemaError::DuplicateEntity {
+                entity: entity.base.to_string(),
+            });
+        }
+
+        self.entities.push(entity);
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Checks the whole schema and reports every problem found, rather than stopping at the first.
+    pub fn validate(&self) -> std::result::Result<(), Vec<SchemaError>> {
+        let mut errors = vec![];
+
+        for (i, arena) in self.arenas.iter().enumerate() {
+            if self.arenas[..i].iter().any(|a| a.name == arena.name) {
+                errors.push(SchemaError::DuplicateArena {
+                    arena: arena.name.to_string(),
+                });
+            }
+
+            let targets = arena
+                .refs
+                .iter()
+                .map(|r| (&r.field, &r.arena))
+                .chain(arena.many_refs.iter().map(|(f, a)| (f, a)));
+
+            for (field, target) in targets {
+                if !self.contains_arena(target) {
+                    errors.push(SchemaError::MissingReferenceTarget {
+                        arena: arena.name.to_string(),
+                        field: field.to_string(),
+                        target: target.to_string(),
+                    });
+                }
+            }
+
+            for r in arena.refs.iter().filter(|r| !r.optional && r.on_delete == ReferentialAction::Nullify) {
+                errors.push(SchemaError::NullifyRequiredReference {
+                    arena: arena.name.to_string(),
+                    field: r.field.to_string(),
+                });
+            }
+
+            let permanent = !self.deletable_arenas.contains(&arena.name);
+            if permanent {
+                errors.extend(Self::get_permanent_cascade_errors(arena));
+            }
+            errors.extend(self.get_reference_errors(arena, permanent));
+            errors.extend(self.get_derived_errors(arena));
+
+            errors.extend(self.get_field_collisions(arena));
+        }
+
+        for (i, entity) in self.entities.iter().enumerate() {
+            if self.entities[..i].iter().any(|e| e.base == entity.base) {
+                errors.push(SchemaError::DuplicateEntity {
+                    entity: entity.base.to_string(),
+                });
+            }
+
+            errors.extend(self.get_entity_errors(entity));
+        }
+
+        if let Err(e) = self.get_derived_update_order() {
+            errors.push(e);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn get_entity_errors(&self, entity: &EntityCore) -> Vec<SchemaError> {
+        let missing = entity
+            .get_arenas()
+            .filter(|a| !self.contains_arena(a))
+            .map(|a| SchemaError::MissingEntityArena {
+                entity: entity.base.to_string(),
+                arena: a.to_string(),
+            });
+
+        let owned = self
+            .arenas
+            .iter()
+            .filter(|a| a.name != entity.base && entity.owns_arena(&a.name))
+            .flat_map(|a| {
+                a.refs
+                    .iter()
+                    .filter(|r| r.on_delete == ReferentialAction::Cascade)
+                    .map(move |r| SchemaError::OwnedCascade {
+                        arena: a.name.to_string(),
+                        field: r.field.to_string(),
+                    })
+            });
+
+        let from_deletable = self.deletable_arenas.contains(&entity.base);
+        let ownership = entity
+            .get_owned_relationships()
+            .filter(|(a, _)| self.contains_arena(a))
+            .filter_map(|(a, relationship)| {
+                entity.get_ownership_error(a, from_deletable, self.deletable_arenas.contains(a), relationship)
+            });
+
+        missing.chain(owned).chain(ownership).collect()
+    }
+
+    // see the ownership matrix in entities.rs, references to arenas that are not inserted yet are skipped
+    fn get_reference_errors<'a>(
+        &'a self,
+        arena: &'a ArenaCore,
+        permanent: bool,
+    ) -> impl Iterator<Item = SchemaError> + 'a {
+        // a cascading required reference to a transient arena is how an owner is declared
+        let required = arena.refs.iter().map(move |r| {
+            let relationship = match (r.optional, r.on_delete) {
+                (true, _) => Relationship::MaybeRef,
+                (false, ReferentialAction::Cascade) if !permanent && self.deletable_arenas.contains(&r.arena) => Relationship::OwnerRef,
+                (false, _) => Relationship::Ref,
+            };
+            (&r.field, &r.arena, relationship)
+        });
+
+        let many = arena.many_refs.iter().map(|(f, a)| (f, a, Relationship::ManyRef));
+
+        required
+            .chain(many)
+            .filter(move |(_, target, _)| self.contains_arena(target))
+            .filter_map(move |(field, target, relationship)| {
+                let to_deletable = self.deletable_arenas.contains(target);
+                get_invalid_relationship(!permanent, to_deletable, relationship).map(|reason| SchemaError::InvalidReference {
+                    arena: arena.name.to_string(),
+                    field: field.to_string(),
+                    target: target.to_string(),
+                    reason: reason.to_string(),
+                })
+            })
+    }
+
+    // a linked input must be an indexed column of the linked arena, and on the same arena it must be declared first
+    // unless it reads the derived component itself through a hierarchy, which is updated parents first
+    fn get_derived_errors<'a>(&'a self, arena: &'a ArenaCore) -> impl Iterator<Item = SchemaError> + 'a {
+        arena
+            .components
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| match &c.source {
+                Source::Derived { inputs, .. } => Some((i, c, inputs)),
+                _ => None,
+            })
+            .flat_map(move |(i, c, inputs)| {
+                inputs
+                    .iter()
+                    .filter(move |input| !self.is_valid_linked_input(arena, i, input))
+                    .map(move |input| SchemaError::InvalidDerivedInput {
+                        arena: arena.name.to_string(),
+                        field: c.field_name.to_string(),
+                        input: input.to_string(),
+                    })
+            })
+    }
+
+    fn is_valid_linked_input(&self, arena: &ArenaCore, index: usize, input: &DerivedInput) -> bool {
+        let link = match &input.link {
+            Some(link) => link,
+            None => return true,
+        };
+
+        let target = match self.get_link_target(arena, link) {
+            Some((target, _optional)) => target,
+            None => return false,
+        };
+
+        // missing arenas are reported by the reference and entity checks
+        let target = match self.try_get_arena(target) {
+            Ok(target) => target,
+            Err(_) => return true,
+        };
+
+        let is_column = |c: &Component| c.field_name == input.field && c.density != Density::Rare;
+
+        if target.name != arena.name {
+            return target.components.iter().any(is_column);
+        }
+
+        arena.components[..index].iter().any(is_column)
+            || (arena.components[index].field_name == input.field
+                && Self::get_derived_hierarchy(arena, std::slice::from_ref(input)).is_some())
+    }
+
+    // arenas are updated after the arenas whose derived components they read through links
+    fn get_derived_update_order(&self) -> std::result::Result<Vec<&ArenaCore>, SchemaError> {
+        let mut order = vec![];
+        let mut visiting = vec![];
+
+        for arena in self.arenas.iter().filter(|a| Self::get_derived_components(a).next().is_some()) {
+            self.visit_derived_arena(arena, &mut visiting, &mut order)?;
+        }
+
+        Ok(order)
+    }
+
+    fn visit_derived_arena<'a>(
+        &'a self,
+        arena: &'a ArenaCore,
+        visiting: &mut Vec<&'a ArenaName>,
+        order: &mut Vec<&'a ArenaCore>,
+    ) -> std::result::Result<(), SchemaError> {
+        if order.iter().any(|a| a.name == arena.name) {
+            return Ok(());
+        }
+
+        if visiting.contains(&&arena.name) {
+            return Err(SchemaError::DerivedCycle {
+                arena: arena.name.to_string(),
+            });
+        }
+
+        visiting.push(&arena.name);
+        for dependency in self.get_derived_dependencies(arena) {
+            self.visit_derived_arena(dependency, visiting, order)?;
+        }
+        visiting.pop();
+
+        order.push(arena);
+        Ok(())
+    }
+
+    fn get_derived_dependencies<'a>(&'a self, arena: &'a ArenaCore) -> impl Iterator<Item = &'a ArenaCore> + 'a {
+        Self::get_derived_components(arena)
+            .flat_map(|(_, _, inputs)| inputs.iter())
+            .filter_map(move |input| {
+                let (target, _optional) = self.get_link_target(arena, input.link.as_ref()?)?;
+                let target = self.try_get_arena(target).ok()?;
+                let is_derived = Self::get_derived_components(target).any(|(c, _, _)| c.field_name == input.field);
+                (target.name != arena.name && is_derived).then_some(target)
+            })
+    }
+
+    // the arena behind a derived input link, and whether the link can be empty
+    fn get_link_target<'a>(&'a self, arena: &'a ArenaCore, link: &SnakeCase) -> Option<(&'a ArenaName, bool)> {
+        let reference = arena
+            .refs
+            .iter()
+            .find(|r| r.field == *link)
+            .map(|r| (&r.arena, r.optional));
+
+        let child = || {
+            self.entities
+                .iter()
+                .filter(|e| e.base == arena.name)
+                .flat_map(|e| e.children.iter())
+                .find(|c| c.as_field_name() == *link)
+                .map(|c| (c, true))
+        };
+
+        let owner = || {
+            self.entities
+                .iter()
+                .filter(|e| e.base != arena.name && e.owns_arena(&arena.name))
+                .find(|e| e.base.as_field_name() == *link)
+                .map(|e| (&e.base, false))
+        };
+
+        reference.or_else(child).or_else(owner)
+    }
+
+    fn get_permanent_cascade_errors(arena: &ArenaCore) -> impl Iterator<Item = SchemaError> + '_ {
+        arena
+            .refs
+            .iter()
+            .filter(|r| r.on_delete == ReferentialAction::Cascade)
+            .map(move |r| SchemaError::PermanentCascade {
+                arena: arena.name.to_string(),
+                field: r.field.to_string(),
+            })
+    }
+
     fn contains_arena(&self, arena_name: &ArenaName) -> bool {
         self.arenas.iter().any(|a| a.name.eq(arena_name))
     }
@@ -170,14 +529,57 @@ impl World {
             .iter()
             .flat_map(|e| self.generate_delete_entity_function(e));
 
+        let entity_get_methods = self
+            .entities
+            .iter()
+            .map(|e| self.generate_get_entity_function(e));
+
+        let entity_child_methods = self
+            .entities
+            .iter()
+            .flat_map(|e| e.children.iter().map(move |c| (e, c)))
+            .flat_map(|(e, c)| {
+                std::iter::once(self.generate_attach_child_function(e, c))
+                    .chain(self.generate_detach_child_function(e, c))
+            });
+
+        let entity_collection_methods = self
+            .entities
+            .iter()
+            .flat_map(|e| e.collections.iter().map(move |c| (e, c)))
+            .filter_map(|(e, c)| self.generate_remove_collection_child_function(e, c));
+
+        let entity_enum_methods = self
+            .entities
+            .iter()
+            .flat_map(|e| e.enums.iter().map(move |ee| (e, ee)))
+            .filter(|(_, ee)| self.has_transient_options(ee))
+            .flat_map(|(e, ee)| vec![
+                self.generate_create_entity_enum_function(e, ee),
+                self.generate_set_entity_enum_function(e, ee),
+            ]);
+
         let arena_functions = self
             .arenas
             .iter()
             .filter_map(|a| self.generate_non_entity_arena_function(a));
 
+        let arena_delete_methods = self
+            .arenas
+            .iter()
+            .filter_map(|a| self.generate_non_entity_delete_function(a));
+
+        let update_derived = self.generate_update_derived_function();
+
         entity_create_methods
             .chain(entity_delete_methods)
+            .chain(entity_get_methods)
+            .chain(entity_child_methods)
+            .chain(entity_collection_methods)
+            .chain(entity_enum_methods)
             .chain(arena_functions)
+            .chain(arena_delete_methods)
+            .chain(update_derived)
             .fold(world_impl, |world, f| world.add_function(f))
     }
 
@@ -219,10 +621,210 @@ impl World {
                     "self.state.{e}.insert(&id, row);",
                     e = arena.name.as_field_name(),
                 ),
-            ))
-            .add_line(CodeLine::new(0, "id"));
+            ));
+
+        let func = self
+            .get_index_line(&arena.name, "self.state", "&id")
+            .into_iter()
+            .fold(func, |func, line| func.add_line(CodeLine::new(0, &line)));
+
+        func.add_line(CodeLine::new(0, "id")).into()
+    }
+
+    fn generate_non_entity_delete_function(&self, arena: &ArenaCore) -> Option<Function> {
+        if !self.deletable_arenas.contains(&arena.name) {
+            return None;
+        }
+
+        if self.entities.iter().any(|e| e.owns_arena(&arena.name)) {
+            return None;
+        }
+
+        let func = self.get_delete_function_head(&arena.name);
+
+        let func = self
+            .get_remove_row_lines(&arena.name, "&id", 1)
+            .into_iter()
+            .fold(func, |func, line| func.add_line(line));
+
+        self.get_delete_function_tail(func, &arena.name).into()
+    }
+
+    fn get_delete_function_head(&self, arena: &ArenaName) -> Function {
+        let a = arena.as_field_name();
+
+        let func = Function::new(&format!("delete_{}", a))
+            .with_parameters(&format!("&mut self, id: {}", self.get_id(arena)))
+            .with_return(format!("Result<(), {}>", DELETE_ERROR))
+            .add_line(CodeLine::new(0, "let (alloc, state) = self.split();\n"));
+
+        let owned: Vec<_> = std::iter::once(arena.clone())
+            .chain(self.get_owned_children(arena))
+            .collect();
+
+        let func = self.get_cascade_declarations(func, &owned);
+
+        let func = func.add_line(CodeLine::new(0, &format!("if let Some(id) = alloc.{a}.validate(id) {{", a=a)));
+
+        let func = if self.needs_delete_check(arena, &mut vec![]) {
+            func.add_line(CodeLine::new(1, &format!("state.can_delete_{}(&id)?;\n", a)))
+        } else {
+            func
+        };
+
+        self.get_referential_action_lines(arena, "&id", 1)
+            .into_iter()
+            .fold(func, |func, line| func.add_line(line))
+    }
+
+    fn get_delete_function_tail(&self, func: Function, arena: &ArenaName) -> Function {
+        let func = func
+            .add_line(CodeLine::new(0, "}\n"))
+            .add_line(CodeLine::new(0, &format!("alloc.{}.kill(id);\n", arena.as_field_name())));
 
-        func.into()
+        let owned: Vec<_> = std::iter::once(arena.clone())
+            .chain(self.get_owned_children(arena))
+            .collect();
+
+        self.get_cascade_deletes(func, &owned)
+            .add_line(CodeLine::new(0, "Ok(())"))
+    }
+
+    // cascaded referrers are collected while the world is split and deleted once it is whole again
+    fn get_cascade_declarations(&self, func: Function, arenas: &[ArenaName]) -> Function {
+        let cascades: Vec<_> = self.get_cascades(arenas).collect();
+
+        let func = cascades.iter().fold(func, |func, (arena, referrer, r)| {
+            func.add_line(CodeLine::new(0, &format!(
+                "let mut {} = vec![];",
+                Self::get_cascade_field(arena, &referrer.name, r),
+            )))
+        });
+
+        if cascades.is_empty() {
+            func
+        } else {
+            func.add_line(CodeLine::new(0, ""))
+        }
+    }
+
+    fn get_cascade_deletes(&self, func: Function, arenas: &[ArenaName]) -> Function {
+        self.get_cascades(arenas)
+            .fold(func, |func, (arena, referrer, r)| {
+                func.add_line(CodeLine::new(0, &format!("for id in {} {{", Self::get_cascade_field(arena, &referrer.name, r))))
+                    .add_line(CodeLine::new(1, &format!("self.delete_{}(id)?;", referrer.name.as_field_name())))
+                    .add_line(CodeLine::new(0, "}\n"))
+            })
+    }
+
+    fn get_cascades<'a>(
+        &'a self,
+        arenas: &'a [ArenaName],
+    ) -> impl Iterator<Item = (&'a ArenaName, &'a ArenaCore, &'a Reference)> + 'a {
+        arenas.iter().flat_map(move |arena| {
+            self.get_incoming_references(arena)
+                .filter(|(_, r)| r.on_delete == ReferentialAction::Cascade)
+                .map(move |(referrer, r)| (arena, referrer, r))
+        })
+    }
+
+    fn get_cascade_field(arena: &ArenaName, referrer: &ArenaName, reference: &Reference) -> String {
+        format!("{}_{}", arena.as_field_name(), Self::get_reverse_index_field(referrer, reference))
+    }
+
+    // nullifies referrers and collects cascades, the world must be split and `id` a valid id of `arena`
+    fn get_referential_action_lines(&self, arena: &ArenaName, id: &str, indent: usize) -> Vec<CodeLine> {
+        let a = arena.as_field_name();
+
+        self.get_incoming_references(arena)
+            .flat_map(|(referrer, r)| {
+                let index = Self::get_reverse_index_field(&referrer.name, r);
+                match r.on_delete {
+                    ReferentialAction::Restrict => vec![],
+                    ReferentialAction::Nullify => vec![
+                        CodeLine::new(indent, &format!("for referrer in state.{a}.{i}[{id}].iter() {{", a=a, i=index, id=id)),
+                        CodeLine::new(indent + 1, &format!(
+                            "state.{r}.{f}[{id}] = None;",
+                            r=referrer.name.as_field_name(),
+                            f=r.field,
+                            id=self.get_valid_id_expr(&referrer.name, "*referrer"),
+                        )),
+                        CodeLine::new(indent, "}\n"),
+                    ],
+                    ReferentialAction::Cascade => vec![CodeLine::new(indent, &format!(
+                        "{v}.extend(std::mem::take(&mut state.{a}.{i}[{id}]));\n",
+                        v=Self::get_cascade_field(arena, &referrer.name, r),
+                        a=a,
+                        i=index,
+                        id=id,
+                    ))],
+                }
+            })
+            .collect()
+    }
+
+    // deletes an owned child row `child` (a GenId) whose link from the parent is already gone
+    fn get_kill_row_lines(&self, arena: &ArenaName, child: &str, indent: usize) -> Vec<CodeLine> {
+        let id = format!("&Valid::new({})", child);
+
+        self.get_referential_action_lines(arena, &id, indent)
+            .into_iter()
+            .chain(self.get_remove_row_lines(arena, &id, indent))
+            .chain(std::iter::once(CodeLine::new(indent, &format!("alloc.{}.kill({});", arena.as_field_name(), child))))
+            .collect()
+    }
+
+    fn get_delete_check_line(&self, arena: &ArenaName, child: &str, indent: usize) -> Option<CodeLine> {
+        if self.needs_delete_check(arena, &mut vec![]) {
+            Some(CodeLine::new(indent, &format!("state.can_delete_{}(&Valid::new({}))?;", arena.as_field_name(), child)))
+        } else {
+            None
+        }
+    }
+
+    // transient arenas whose rows are deleted along with a row of `arena`
+    fn get_owned_children(&self, arena: &ArenaName) -> Vec<ArenaName> {
+        self.get_entity(arena)
+            .map(|e| {
+                e.children
+                    .iter()
+                    .chain(e.collections.iter())
+                    .chain(e.enums.iter().flat_map(|ee| ee.options.iter()))
+                    .filter(|c| self.deletable_arenas.contains(c))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn get_remove_row_lines(&self, arena: &ArenaName, id: &str, indent: usize) -> Vec<CodeLine> {
+        let a = arena.as_field_name();
+
+        let unindex = if !self.get_arena(arena).refs.is_empty() {
+            Some(CodeLine::new(indent, &format!("state.unindex_{}({});", a, id)))
+        } else {
+            None
+        };
+
+        let prune = if self.has_many_references(arena) {
+            Some(CodeLine::new(indent, &format!("state.prune_{}_links({});", a, id)))
+        } else {
+            None
+        };
+
+        unindex
+            .into_iter()
+            .chain(prune)
+            .chain(std::iter::once(CodeLine::new(indent, &format!("state.{}.remove({});", a, id))))
+            .collect()
+    }
+
+    fn get_index_line(&self, arena: &ArenaName, state: &str, id: &str) -> Option<String> {
+        if !self.get_arena(arena).refs.is_empty() {
+            Some(format!("{}.index_{}({});", state, arena.as_field_name(), id))
+        } else {
+            None
+        }
     }
 
     fn generate_create_entity_function(&self, entity: &EntityCore) -> Function {
@@ -232,8 +834,16 @@ impl World {
             .with_parameters(&format!("&mut self, entity: {}", entity.name()))
             .with_return(self.get_valid_id(&entity.base).to_string())
             .add_line(CodeLine::new(0, "let (alloc, state) = self.split();"))
-            .add_line(CodeLine::new(0, ""))
-            .add_line(CodeLine::new(0, &format!("let id = state.{e}.create(entity.{e}, &mut alloc.{e});\n", e = e)));
+            .add_line(CodeLine::new(0, ""));
+
+        let create = format!("let id = state.{e}.create(entity.{e}, &mut alloc.{e});", e = e);
+
+        let func = match self.get_index_line(&entity.base, "state", "&id") {
+            Some(line) => func
+                .add_line(CodeLine::new(0, &create))
+                .add_line(CodeLine::new(0, &format!("{}\n", line))),
+            None => func.add_line(CodeLine::new(0, &format!("{}\n", create))),
+        };
 
         let func = entity
             .children
@@ -242,9 +852,29 @@ impl World {
             .fold(func, |func, child| {
                 let c = child.name.as_field_name();
 
-                func.add_line(CodeLine::new(0, &format!("if let Some({c}) = entity.{c} {{", c=c)))
+                let func = func.add_line(CodeLine::new(0, &format!("if let Some({c}) = entity.{c} {{", c=c)))
+                    .add_line(CodeLine::new(1, &format!("let {c} = state.{c}.create({c}, &mut alloc.{c});", c=c)))
+                    .add_line(CodeLine::new(1, &format!("state.link_{e}_to_{c}(&id, &{c});", e=e, c=c)));
+
+                self.get_index_line(&child.name, "state", &format!("&{}", c))
+                    .into_iter()
+                    .fold(func, |func, line| func.add_line(CodeLine::new(1, &line)))
+                    .add_line(CodeLine::new(0, "}\n"))
+            });
+
+        let func = entity
+            .collections
+            .iter()
+            .fold(func, |func, child| {
+                let c = child.as_field_name();
+
+                let func = func.add_line(CodeLine::new(0, &format!("for {c} in entity.{c} {{", c=c)))
                     .add_line(CodeLine::new(1, &format!("let {c} = state.{c}.create({c}, &mut alloc.{c});", c=c)))
-                    .add_line(CodeLine::new(1, &format!("state.link_{e}_to_{c}(&id, &{c});", e=e, c=c)))
+                    .add_line(CodeLine::new(1, &format!("state.add_{c}_to_{e}(&id, &{c});", e=e, c=c)));
+
+                self.get_index_line(child, "state", &format!("&{}", c))
+                    .into_iter()
+                    .fold(func, |func, line| func.add_line(CodeLine::new(1, &line)))
                     .add_line(CodeLine::new(0, "}\n"))
             });
 
@@ -256,9 +886,13 @@ impl World {
                     .add_line(CodeLine::new(0, &format!("match entity.{} {{", entity_enum.name.into_snake_case())));
 
                 entity_enum.options.iter().fold(func, |func, opt| {
-                    func.add_line(CodeLine::new(1, &format!("{}Row::{}(row) => {{", entity_enum.name, opt)))
+                    let func = func.add_line(CodeLine::new(1, &format!("{}Row::{}(row) => {{", entity_enum.name, opt)))
                         .add_line(CodeLine::new(2, &format!("let {c} = state.{c}.create(row, &mut alloc.{c});", c=opt.as_field_name())))
-                        .add_line(CodeLine::new(2, &format!("state.link_{e}_to_{c}(&id, &{c});", e=e, c=opt.as_field_name())))
+                        .add_line(CodeLine::new(2, &format!("state.link_{e}_to_{c}(&id, &{c});", e=e, c=opt.as_field_name())));
+
+                    self.get_index_line(opt, "state", &format!("&{}", opt.as_field_name()))
+                        .into_iter()
+                        .fold(func, |func, line| func.add_line(CodeLine::new(2, &line)))
                         .add_line(CodeLine::new(1, "}"))
                 })
                     .add_line(CodeLine::new(0, "}"))
@@ -275,41 +909,399 @@ impl World {
 
         let e = entity.base.as_field_name();
 
-        let func = Function::new(&format!("delete_{}", entity.base.as_field_name()))
-            .with_parameters(&format!("&mut self, id: {}", self.get_id(&entity.base)))
-            .add_line(CodeLine::new(0, "let (alloc, state) = self.split();\n"))
-            .add_line(CodeLine::new(0, &format!("if let Some(id) = alloc.{e}.validate(id) {{", e=e)));
+        let func = self.get_delete_function_head(&entity.base);
 
         let func = entity.children.iter().fold(func, |func, child| {
-            let c = child.as_field_name();
-            func.add_line(CodeLine::new(1, &format!("if let Some(child) = state.{e}.{c}.get_opt(&id) {{", e=e, c=c)))
-                .add_line(CodeLine::new(2, &format!("alloc.{c}.kill(*child);", c=c)))
+            let func = func.add_line(CodeLine::new(1, &format!(
+                "if let Some(child) = state.{e}.{c}.get_opt(&id).copied() {{",
+                e=e,
+                c=child.as_field_name(),
+            )));
+
+            self.get_kill_row_lines(child, "child", 2)
+                .into_iter()
+                .fold(func, |func, line| func.add_line(line))
                 .add_line(CodeLine::new(1, "}\n"))
         });
 
-        let func = entity.enums.iter().fold(func, |func, entity_enum| {
+        let func = entity.collections.iter().fold(func, |func, child| {
+            let func = func.add_line(CodeLine::new(1, &format!(
+                "for child in std::mem::take(&mut state.{e}.{c}[&id]) {{",
+                e=e,
+                c=child.as_field_name(),
+            )));
+
+            self.get_kill_row_lines(child, "child", 2)
+                .into_iter()
+                .fold(func, |func, line| func.add_line(line))
+                .add_line(CodeLine::new(1, "}\n"))
+        });
+
+        let func = entity.enums.iter().fold(func, |func, entity_enum| {
             let ee = entity_enum.name.into_snake_case();
-            let func = func.add_line(CodeLine::new(1, &format!("match state.{e}.{ee}.get(&id) {{", e=e, ee=ee)));
+            let func = func.add_line(CodeLine::new(1, &format!("match state.{e}.{ee}.get(&id).copied() {{", e=e, ee=ee)));
 
             let func = entity_enum.options.iter()
                 .fold(func, |func, opt| {
-                    func.add_line(CodeLine::new(2, &format!(
-                        "Some({enum_name}::{opt}(child)) => alloc.{o}.kill(*child),",
+                    let func = func.add_line(CodeLine::new(2, &format!(
+                        "Some({enum_name}::{opt}(child)) => {{",
                         enum_name=entity_enum.name,
                         opt=opt,
-                        o=opt.as_field_name(),
-                    )))
+                    )));
+
+                    self.get_kill_row_lines(opt, "child", 3)
+                        .into_iter()
+                        .fold(func, |func, line| func.add_line(line))
+                        .add_line(CodeLine::new(2, "}"))
                 });
 
             func.add_line(CodeLine::new(2, "None => {},"))
-                .add_line(CodeLine::new(1, "}"))
+                .add_line(CodeLine::new(1, "}\n"))
+        });
+
+        let func = self
+            .get_remove_row_lines(&entity.base, "&id", 1)
+            .into_iter()
+            .fold(func, |func, line| func.add_line(line));
+
+        self.get_delete_function_tail(func, &entity.base).into()
+    }
+
+    fn generate_get_entity_function(&self, entity: &EntityCore) -> Function {
+        let e = entity.base.as_field_name();
+        let deletable = self.deletable_arenas.contains(&entity.base);
+
+        let func = Function::new(&format!("get_{}", e))
+            .with_parameters(&format!("&self, id: {}", self.get_id(&entity.base)));
+
+        let func = if deletable {
+            func.with_return(format!("Option<{}>", entity.name()))
+                .add_line(CodeLine::new(0, &format!("let id = self.allocators.{}.validate(id)?;", e)))
+        } else {
+            func.with_return(entity.name().to_string())
+        };
+
+        let entity_struct = format!("{} {{", entity.name());
+
+        let func = func
+            .add_line(CodeLine::new(0, "let state = &self.state;\n"))
+            .add_line(CodeLine::new(0, &if deletable { format!("Some({}", entity_struct) } else { entity_struct }))
+            .add_line(CodeLine::new(1, &format!("{e}: state.get_{e}_row(&id),", e=e)));
+
+        let func = entity.children.iter().fold(func, |func, child| {
+            func.add_line(CodeLine::new(1, &format!(
+                "{c}: state.{e}.{c}[&id].as_ref().map(|c| state.get_{c}_row({id})),",
+                e=e,
+                c=child.as_field_name(),
+                id=self.get_child_ref_expr(child),
+            )))
+        });
+
+        let func = entity.collections.iter().fold(func, |func, child| {
+            func.add_line(CodeLine::new(1, &format!(
+                "{c}: state.{e}.{c}[&id].iter().map(|c| state.get_{c}_row({id})).collect(),",
+                e=e,
+                c=child.as_field_name(),
+                id=self.get_child_ref_expr(child),
+            )))
+        });
+
+        let func = entity.enums.iter().fold(func, |func, entity_enum| {
+            let ee = entity_enum.name.into_snake_case();
+            let func = func.add_line(CodeLine::new(1, &format!("{ee}: match &state.{e}.{ee}[&id] {{", e=e, ee=ee)));
+
+            entity_enum.options.iter()
+                .fold(func, |func, opt| {
+                    func.add_line(CodeLine::new(2, &format!(
+                        "{name}::{opt}(c) => {name}Row::{opt}(state.get_{o}_row({id})),",
+                        name=entity_enum.name,
+                        opt=opt,
+                        o=opt.as_field_name(),
+                        id=self.get_child_ref_expr(opt),
+                    )))
+                })
+                .add_line(CodeLine::new(1, "},"))
         });
 
-        func.add_line(CodeLine::new(0, "}\n"))
-            .add_line(CodeLine::new(0, &format!("alloc.{e}.kill(id);", e=e)))
+        if deletable {
+            func.add_line(CodeLine::new(0, "})"))
+        } else {
+            func.add_line(CodeLine::new(0, "}"))
+        }
+    }
+
+    // a child id `c` that is borrowed from a component, as the id reference taken by State::get_<arena>_row
+    fn get_child_ref_expr(&self, child: &ArenaName) -> String {
+        if self.deletable_arenas.contains(child) {
+            "&Valid::new(*c)".to_string()
+        } else {
+            "c".to_string()
+        }
+    }
+
+    fn generate_attach_child_function(&self, entity: &EntityCore, child: &ArenaName) -> Function {
+        let e = entity.base.as_field_name();
+        let c = child.as_field_name();
+
+        let func = Function::new(&format!("attach_{}_to_{}", c, e))
+            .with_parameters(&format!("&mut self, id: {}, row: {}", self.get_id(&entity.base), child.get_row_type()))
+            .with_return(format!("Result<{}, {}>", self.get_valid_id(child), child.get_row_type()))
+            .add_line(CodeLine::new(0, "let (alloc, state) = self.split();\n"));
+
+        let func = if self.deletable_arenas.contains(&entity.base) {
+            func.add_line(CodeLine::new(0, &format!("let id = match alloc.{}.validate(id) {{", e)))
+                .add_line(CodeLine::new(1, "Some(id) => id,"))
+                .add_line(CodeLine::new(1, "None => return Err(row),"))
+                .add_line(CodeLine::new(0, "};\n"))
+        } else {
+            func
+        };
+
+        let func = func
+            .add_line(CodeLine::new(0, &format!("if state.{e}.{c}[&id].is_some() {{", e=e, c=c)))
+            .add_line(CodeLine::new(1, "return Err(row);"))
+            .add_line(CodeLine::new(0, "}\n"))
+            .add_line(CodeLine::new(0, &format!("let {c} = state.{c}.create(row, &mut alloc.{c});", c=c)));
+
+        let link = format!("state.link_{e}_to_{c}(&id, &{c});", e=e, c=c);
+
+        let func = match self.get_index_line(child, "state", &format!("&{}", c)) {
+            Some(line) => func
+                .add_line(CodeLine::new(0, &link))
+                .add_line(CodeLine::new(0, &format!("{}\n", line))),
+            None => func.add_line(CodeLine::new(0, &format!("{}\n", link))),
+        };
+
+        func.add_line(CodeLine::new(0, &format!("Ok({})", c)))
+    }
+
+    // permanent children cannot be killed, so they stay attached once created
+    fn generate_detach_child_function(&self, entity: &EntityCore, child: &ArenaName) -> Option<Function> {
+        if !self.deletable_arenas.contains(child) {
+            return None;
+        }
+
+        let e = entity.base.as_field_name();
+        let c = child.as_field_name();
+
+        let func = Function::new(&format!("detach_{}_from_{}", c, e))
+            .with_parameters(&format!("&mut self, id: {}", self.get_id(&entity.base)))
+            .with_return(format!("Result<Option<{}>, {}>", self.get_id(child), DELETE_ERROR))
+            .add_line(CodeLine::new(0, "let (alloc, state) = self.split();\n"));
+
+        let func = self.get_cascade_declarations(func, std::slice::from_ref(child));
+
+        let func = if self.deletable_arenas.contains(&entity.base) {
+            func.add_line(CodeLine::new(0, &format!("let id = match alloc.{}.validate(id) {{", e)))
+                .add_line(CodeLine::new(1, "Some(id) => id,"))
+                .add_line(CodeLine::new(1, "None => return Ok(None),"))
+                .add_line(CodeLine::new(0, "};\n"))
+        } else {
+            func
+        };
+
+        let func = func
+            .add_line(CodeLine::new(0, &format!("let child = match state.{e}.{c}[&id] {{", e=e, c=c)))
+            .add_line(CodeLine::new(1, "Some(child) => child,"))
+            .add_line(CodeLine::new(1, "None => return Ok(None),"))
+            .add_line(CodeLine::new(0, "};\n"));
+
+        let func = match self.get_delete_check_line(child, "child", 0) {
+            Some(line) => func.add_line(line),
+            None => func,
+        };
+
+        let func = func.add_line(CodeLine::new(0, &format!("state.{e}.{c}[&id] = None;\n", e=e, c=c)));
+
+        let func = self
+            .get_kill_row_lines(child, "child", 0)
+            .into_iter()
+            .fold(func, |func, line| func.add_line(line))
+            .add_line(CodeLine::new(0, ""));
+
+        self.get_cascade_deletes(func, std::slice::from_ref(child))
+            .add_line(CodeLine::new(0, "Ok(Some(child))"))
+            .into()
+    }
+
+    // permanent collection rows cannot be killed either, so they are only ever added
+    fn generate_remove_collection_child_function(&self, entity: &EntityCore, child: &ArenaName) -> Option<Function> {
+        if !self.deletable_arenas.contains(child) {
+            return None;
+        }
+
+        let e = entity.base.as_field_name();
+        let c = child.as_field_name();
+
+        let func = Function::new(&format!("remove_{}_from_{}", c, e))
+            .with_parameters(&format!("&mut self, id: {}, child: {}", self.get_id(&entity.base), self.get_id(child)))
+            .with_return(format!("Result<Option<{}>, {}>", self.get_id(child), DELETE_ERROR))
+            .add_line(CodeLine::new(0, "let (alloc, state) = self.split();\n"));
+
+        let func = self.get_cascade_declarations(func, std::slice::from_ref(child));
+
+        let func = if self.deletable_arenas.contains(&entity.base) {
+            func.add_line(CodeLine::new(0, &format!("let id = match alloc.{}.validate(id) {{", e)))
+                .add_line(CodeLine::new(1, "Some(id) => id,"))
+                .add_line(CodeLine::new(1, "None => return Ok(None),"))
+                .add_line(CodeLine::new(0, "};\n"))
+        } else {
+            func
+        };
+
+        let func = func
+            .add_line(CodeLine::new(0, &format!("if !state.{e}.{c}[&id].contains(&child) {{", e=e, c=c)))
+            .add_line(CodeLine::new(1, "return Ok(None);"))
+            .add_line(CodeLine::new(0, "}\n"));
+
+        let func = match self.get_delete_check_line(child, "child", 0) {
+            Some(line) => func.add_line(line),
+            None => func,
+        };
+
+        let func = func.add_line(CodeLine::new(0, &format!("state.{e}.{c}[&id].retain(|c| *c != child);\n", e=e, c=c)));
+
+        let func = self
+            .get_kill_row_lines(child, "child", 0)
+            .into_iter()
+            .fold(func, |func, line| func.add_line(line))
+            .add_line(CodeLine::new(0, ""));
+
+        self.get_cascade_deletes(func, std::slice::from_ref(child))
+            .add_line(CodeLine::new(0, "Ok(Some(child))"))
             .into()
     }
 
+    // only fills an empty slot, set_ replaces an existing variant
+    fn generate_create_entity_enum_function(&self, entity: &EntityCore, entity_enum: &EntityEnumCore) -> Function {
+        let e = entity.base.as_field_name();
+        let ee = entity_enum.name.into_snake_case();
+
+        let func = Function::new(&format!("create_{}", ee))
+            .with_parameters(&format!(
+                "&mut self, id: {}, row: {}",
+                self.get_id(&entity.base),
+                entity_enum.get_row_type()
+            ))
+            .with_return(format!("Option<{}>", entity_enum.name))
+            .add_line(CodeLine::new(0, "let (alloc, state) = self.split();\n"));
+
+        let func = if self.deletable_arenas.contains(&entity.base) {
+            func.add_line(CodeLine::new(0, &format!("let id = alloc.{}.validate(id)?;\n", e)))
+        } else {
+            func
+        };
+
+        let func = func
+            .add_line(CodeLine::new(0, &format!("if state.{e}.{ee}.get(&id).is_some() {{", e=e, ee=ee)))
+            .add_line(CodeLine::new(1, "return None;"))
+            .add_line(CodeLine::new(0, "}\n"));
+
+        self.get_create_entity_enum_lines(func, entity, entity_enum)
+            .add_line(CodeLine::new(0, "Some(child)"))
+    }
+
+    fn get_create_entity_enum_lines(&self, func: Function, entity: &EntityCore, entity_enum: &EntityEnumCore) -> Function {
+        let e = entity.base.as_field_name();
+        let func = func.add_line(CodeLine::new(0, "let child = match row {"));
+
+        let func = entity_enum.options.iter().fold(func, |func, opt| {
+            let c = opt.as_field_name();
+            let func = func.add_line(CodeLine::new(1, &format!("{}Row::{}(row) => {{", entity_enum.name, opt)))
+                .add_line(CodeLine::new(2, &format!("let {c} = state.{c}.create(row, &mut alloc.{c});", c=c)))
+                .add_line(CodeLine::new(2, &format!("state.link_{e}_to_{c}(&id, &{c});", e=e, c=c)));
+
+            self.get_index_line(opt, "state", &format!("&{}", c))
+                .into_iter()
+                .fold(func, |func, line| func.add_line(CodeLine::new(2, &line)))
+                .add_line(CodeLine::new(2, &format!("{}::from({})", entity_enum.name, c)))
+                .add_line(CodeLine::new(1, "}"))
+        });
+
+        func.add_line(CodeLine::new(0, "};\n"))
+    }
+
+    fn generate_set_entity_enum_function(&self, entity: &EntityCore, entity_enum: &EntityEnumCore) -> Function {
+        let e = entity.base.as_field_name();
+        let ee = entity_enum.name.into_snake_case();
+        let deletable = self.deletable_arenas.contains(&entity.base);
+
+        let return_type = if deletable {
+            format!("Result<Option<{}>, {}>", entity_enum.name, DELETE_ERROR)
+        } else {
+            format!("Result<{}, {}>", entity_enum.name, DELETE_ERROR)
+        };
+
+        let func = Function::new(&format!("set_{}", ee))
+            .with_parameters(&format!(
+                "&mut self, id: {}, row: {}",
+                self.get_id(&entity.base),
+                entity_enum.get_row_type()
+            ))
+            .with_return(return_type)
+            .add_line(CodeLine::new(0, "let (alloc, state) = self.split();\n"));
+
+        let func = self.get_cascade_declarations(func, &entity_enum.options);
+
+        let func = if deletable {
+            func.add_line(CodeLine::new(0, &format!("let id = match alloc.{}.validate(id) {{", e)))
+                .add_line(CodeLine::new(1, "Some(id) => id,"))
+                .add_line(CodeLine::new(1, "None => return Ok(None),"))
+                .add_line(CodeLine::new(0, "};\n"))
+        } else {
+            func
+        };
+
+        let func = func.add_line(CodeLine::new(0, &format!("let old = state.{e}.{ee}.get(&id).copied();\n", e=e, ee=ee)));
+
+        // the old variant must be deletable before anything is changed
+        let checks: Vec<_> = entity_enum.options.iter()
+            .filter_map(|opt| self.get_delete_check_line(opt, "child", 1).map(|line| (opt, line)))
+            .collect();
+
+        let func = if checks.is_empty() {
+            func
+        } else {
+            let func = func.add_line(CodeLine::new(0, "match old {"));
+            let func = checks.into_iter().fold(func, |func, (opt, line)| {
+                func.add_line(CodeLine::new(1, &format!("Some({}::{}(child)) => {{", entity_enum.name, opt)))
+                    .add_line(line)
+                    .add_line(CodeLine::new(1, "}"))
+            });
+            func.add_line(CodeLine::new(1, "_ => {},"))
+                .add_line(CodeLine::new(0, "}\n"))
+        };
+
+        let func = func.add_line(CodeLine::new(0, "match old {"));
+
+        let func = entity_enum.options.iter().fold(func, |func, opt| {
+            let func = func.add_line(CodeLine::new(1, &format!(
+                "Some({enum_name}::{opt}(child)) => {{",
+                enum_name=entity_enum.name,
+                opt=opt,
+            )));
+
+            self.get_kill_row_lines(opt, "child", 2)
+                .into_iter()
+                .fold(func, |func, line| func.add_line(line))
+                .add_line(CodeLine::new(1, "}"))
+        });
+
+        let func = func
+            .add_line(CodeLine::new(1, "None => {},"))
+            .add_line(CodeLine::new(0, "}\n"));
+
+        let func = self.get_create_entity_enum_lines(func, entity, entity_enum);
+
+        let func = self.get_cascade_deletes(func, &entity_enum.options);
+
+        if deletable {
+            func.add_line(CodeLine::new(0, "Ok(Some(child))"))
+        } else {
+            func.add_line(CodeLine::new(0, "Ok(child)"))
+        }
+    }
+
     pub fn generate_allocators(&self) -> Struct {
         let fields = self
             .arenas
@@ -385,33 +1377,35 @@ impl World {
     }
 
     pub fn generate_arena(&self, arena: &ArenaCore) -> Struct {
-        let component_fields = arena.components.iter().map(|comp| Field {
-            visibility: Pub,
-            name: comp.field_name.clone(),
-            field_type: comp.get_component_type(),
-        });
+        let fields = self
+            .get_arena_fields(arena)
+            .into_iter()
+            .map(|(field, _origin)| field)
+            .collect();
 
-        let entity_enums = self
-            .entities
-            .iter()
-            .filter(|e| e.base.eq(&arena.name))
-            .flat_map(|e| e.enums.iter())
-            .map(|e| Field {
-                visibility: Pub,
-                name: e.name.into_snake_case(),
-                field_type: e.get_component_type()
-            });
+        Struct::new(arena.name.as_str())
+            .with_fields(fields)
+            .with_derives(Derives::with_debug_default_clone())
+    }
 
-        let own_links = self
-            .entities
-            .iter()
-            .filter(|e| e.base.eq(&arena.name))
-            .flat_map(|e| e.children.iter())
-            .map(|c| Field {
-                visibility: Pub,
-                name: c.as_field_name(),
-                field_type: Type::new(&format!("Component<Self,Option<{}>>", self.get_id(c))),
-            });
+    /// Lists the field names of the generated arena struct, in the same order as `generate_arena`.
+    pub fn get_arena_field_origins(&self, arena: &ArenaCore) -> Vec<(SnakeCase, FieldOrigin)> {
+        self.get_arena_fields(arena)
+            .into_iter()
+            .map(|(field, origin)| (field.name, origin))
+            .collect()
+    }
+
+    // the single source of arena struct fields, so collisions are checked against what is generated
+    fn get_arena_fields(&self, arena: &ArenaCore) -> Vec<(Field, FieldOrigin)> {
+        let field = |name: SnakeCase, field_type: Type| Field {
+            visibility: Pub,
+            name,
+            field_type,
+        };
+        // collisions are checked while validating, before missing arenas are reported, so fall back to the name
+        let get_id = |arena: &ArenaName| self.try_get_id(arena).unwrap_or_else(|_| Type::new(arena.as_str()));
+        let owned_entities = || self.entities.iter().filter(move |e| e.base.eq(&arena.name));
 
         let entity_links = self
             .entities
@@ -424,21 +1418,110 @@ impl World {
                     .map(move |c| (e, c))
             })
             .filter(|(_e, c)| arena.name.eq(c))
-            .map(|(e, _c)| Field {
-                visibility: Pub,
-                name: e.base.as_field_name(),
-                field_type: Type::new(&format!("Component<Self,{}>", self.get_id(&e.base))),
-            });
+            .map(|(e, _c)| (
+                field(e.base.as_field_name(), Type::new(&format!("Component<Self,{}>", get_id(&e.base)))),
+                FieldOrigin::Owner { entity: e.base.to_string() },
+            ));
+
+        let components = arena.components.iter().map(|c| {
+            let is_reference = |r: &&Reference| {
+                r.field == c.field_name
+                    && self.id.get(&r.arena).map(|id| id.to_string()) == Some(c.comp_type.to_string())
+            };
+            // late references are set after the row is created, so they start out empty
+            let origin = match arena.refs.iter().find(is_reference) {
+                Some(r) if c.source == Source::ByDefault => FieldOrigin::LateReference { target: r.arena.to_string() },
+                Some(r) => FieldOrigin::Reference { target: r.arena.to_string() },
+                None => FieldOrigin::Component,
+            };
+            (field(c.field_name.clone(), c.get_storage_type(&get_id(&arena.name))), origin)
+        });
+
+        let many_refs = arena.many_refs.iter().map(|(name, target)| (
+            field(name.clone(), Type::new(&format!("Component<Self,Vec<{}>>", get_id(target)))),
+            FieldOrigin::ManyReference { target: target.to_string() },
+        ));
+
+        let tags = arena
+            .tags
+            .iter()
+            .map(|name| (field(name.clone(), Type::new(&format!("TagComponent<{}>", get_id(&arena.name)))), FieldOrigin::Tag));
+
+        let entity_enums = owned_entities()
+            .flat_map(|e| e.enums.iter())
+            .map(|e| (
+                field(e.name.into_snake_case(), e.get_component_type()),
+                FieldOrigin::EntityEnum { name: e.name.to_string() },
+            ));
+
+        let own_links = owned_entities()
+            .flat_map(|e| e.children.iter())
+            .map(|c| (
+                field(c.as_field_name(), Type::new(&format!("Component<Self,Option<{}>>", get_id(c)))),
+                FieldOrigin::Child { arena: c.to_string() },
+            ));
 
-        let fields = entity_links
-            .chain(component_fields)
+        let own_collections = owned_entities()
+            .flat_map(|e| e.collections.iter())
+            .map(|c| (
+                field(c.as_field_name(), Type::new(&format!("Component<Self,Vec<{}>>", get_id(c)))),
+                FieldOrigin::Collection { arena: c.to_string() },
+            ));
+
+        let reverse_indexes = self.get_incoming_references(&arena.name).map(|(referrer, r)| (
+            field(
+                Self::get_reverse_index_field(&referrer.name, r),
+                Type::new(&format!("Component<Self,Vec<{}>>", get_id(&referrer.name))),
+            ),
+            FieldOrigin::ReverseIndex {
+                referrer: referrer.name.to_string(),
+                field: r.field.to_string(),
+            },
+        ));
+
+        let many_reverse_indexes = self.get_incoming_many_references(&arena.name).map(|(referrer, name)| (
+            field(
+                Self::get_reverse_field(&referrer.name, name, &arena.name),
+                Type::new(&format!("Component<Self,Vec<{}>>", get_id(&referrer.name))),
+            ),
+            FieldOrigin::ReverseIndex {
+                referrer: referrer.name.to_string(),
+                field: name.to_string(),
+            },
+        ));
+
+        entity_links
+            .chain(components)
+            .chain(many_refs)
+            .chain(tags)
             .chain(entity_enums)
             .chain(own_links)
-            .collect();
+            .chain(own_collections)
+            .chain(reverse_indexes)
+            .chain(many_reverse_indexes)
+            .collect()
+    }
 
-        Struct::new(arena.name.as_str())
-            .with_fields(fields)
-            .with_derives(Derives::with_debug_default_clone())
+    pub fn get_field_collisions(&self, arena: &ArenaCore) -> Vec<SchemaError> {
+        let fields = self.get_arena_field_origins(arena);
+
+        fields
+            .iter()
+            .enumerate()
+            .filter(|(i, (name, _))| {
+                fields[..*i].iter().all(|(n, _)| n != name)
+                    && fields[i + 1..].iter().any(|(n, _)| n == name)
+            })
+            .map(|(_, (name, _))| SchemaError::FieldCollision {
+                arena: arena.name.to_string(),
+                field: name.to_string(),
+                origins: fields
+                    .iter()
+                    .filter(|(n, _)| n == name)
+                    .map(|(_, o)| o.clone())
+                    .collect(),
+            })
+            .collect()
     }
 
     pub fn generate_state(&self) -> StructType {
@@ -472,11 +1555,159 @@ impl World {
         // link entity enums
         let child_enum_links = self.generate_entity_enum_link_functions();
 
+        // link entity collections
+        let collection_links = self.generate_entity_collection_link_functions();
+
+        // maintain reverse reference indexes
+        let reference_indexes = self.generate_reference_index_functions();
+
+        // walk self linked hierarchies
+        let hierarchies = self.generate_hierarchy_functions();
+
+        // recompute derived components
+        let derived_updates = self.generate_derived_update_functions();
+
+        // link tables for many references
+        let many_links = self.generate_many_reference_functions();
+        let many_prunes = self.generate_many_reference_prune_functions();
+
+        // check referential actions before deletion
+        let delete_checks = self.generate_delete_check_functions();
+
+        // read rows back out of the arenas
+        let row_getters = self.arenas.iter().map(move |a| self.generate_get_row_function(a));
+
+        // walk references across arenas
+        let reference_traversals = self.generate_reference_traversal_functions();
+        let reference_joins = self.generate_reference_join_functions();
+
         entity_child_links
             .chain(child_enum_links)
+            .chain(collection_links)
+            .chain(reference_indexes)
+            .chain(hierarchies)
+            .chain(derived_updates)
+            .chain(many_links)
+            .chain(many_prunes)
+            .chain(delete_checks)
+            .chain(row_getters)
+            .chain(reference_traversals)
+            .chain(reference_joins)
             .fold(state_impl, |state_impl, f| state_impl.add_function(f))
     }
 
+    fn generate_get_row_function(&self, arena: &ArenaCore) -> Function {
+        let a = arena.name.as_field_name();
+
+        let func = Function::new(&format!("get_{}_row", a))
+            .with_parameters(&format!("&self, id: &{}", self.get_valid_id(&arena.name)))
+            .with_return(arena.name.get_row_type().to_string())
+            .add_line(CodeLine::new(0, &format!("{} {{", arena.name.get_row_type())));
+
+        arena
+            .components
+            .iter()
+            .filter(|c| c.source == Source::ByValue)
+            .fold(func, |func, c| {
+                let value = match c.density {
+                    Density::Rare => format!("self.{}.{}.get(id).cloned()", a, c.field_name),
+                    _ => format!("self.{}.{}[id].clone()", a, c.field_name),
+                };
+                func.add_line(CodeLine::new(1, &format!("{}: {},", c.field_name, value)))
+            })
+            .add_line(CodeLine::new(0, "}"))
+    }
+
+    // a.first.second -> id of the arena referenced by second
+    fn generate_reference_traversal_functions(&self) -> impl Iterator<Item=Function> + '_ {
+        self.arenas
+            .iter()
+            .flat_map(|a| a.refs.iter().map(move |r| (a, r)))
+            .flat_map(move |(a, first)| {
+                self.get_arena(&first.arena)
+                    .refs
+                    .iter()
+                    .map(move |second| self.generate_reference_traversal_function(a, first, second))
+            })
+    }
+
+    fn generate_reference_traversal_function(&self, arena: &ArenaCore, first: &Reference, second: &Reference) -> Function {
+        let target = self.get_id(&second.arena).to_string();
+        let optional = first.optional || second.optional;
+
+        let next = if first.optional {
+            format!("let {f} = self.{a}.{f}[id]?;", a=arena.name.as_field_name(), f=first.field)
+        } else {
+            format!("let {f} = self.{a}.{f}[id];", a=arena.name.as_field_name(), f=first.field)
+        };
+
+        let target_id = self.get_ref_id_expr(&first.arena, &first.field.to_string());
+
+        let value = format!(
+            "self.{b}.{g}[{id}]",
+            b=first.arena.as_field_name(),
+            g=second.field,
+            id=target_id,
+        );
+
+        let value = if first.optional && !second.optional {
+            format!("Some({})", value)
+        } else {
+            value
+        };
+
+        Function::new(&format!("{}_{}_{}", arena.name.as_field_name(), first.field, second.field))
+            .with_parameters(&format!("&self, id: &{}", self.get_valid_id(&arena.name)))
+            .with_return(if optional { format!("Option<{}>", target) } else { target })
+            .add_line(CodeLine::new(0, &next))
+            .add_line(CodeLine::new(0, &value))
+    }
+
+    // iterates a referrer row alongside the row it references
+    fn generate_reference_join_functions(&self) -> impl Iterator<Item=Function> + '_ {
+        self.arenas
+            .iter()
+            .flat_map(|a| a.refs.iter().map(move |r| (a, r)))
+            .filter(move |(_, r)| Self::get_view_components(self.get_arena(&r.arena)).next().is_some())
+            .map(move |(a, r)| self.generate_reference_join_function(a, r))
+    }
+
+    fn generate_reference_join_function(&self, arena: &ArenaCore, reference: &Reference) -> Function {
+        let a = arena.name.as_field_name();
+        let target_view = format!("{}<'a>", Self::get_row_view_type(&reference.arena, "Ref"));
+
+        let b = reference.arena.as_field_name();
+        let f = &reference.field;
+
+        let (target_type, target) = match (reference.optional, self.deletable_arenas.contains(&reference.arena)) {
+            (false, false) => (target_view, format!("self.{}.get(row.{})", b, f)),
+            (false, true) => (target_view, format!("self.{}.get(&Valid::new(*row.{}))", b, f)),
+            (true, false) => (format!("Option<{}>", target_view), format!("row.{}.map(|id| self.{}.get(&id))", f, b)),
+            (true, true) => (format!("Option<{}>", target_view), format!("row.{}.map(|id| self.{}.get(&Valid::new(id)))", f, b)),
+        };
+
+        let rows = if self.deletable_arenas.contains(&arena.name) {
+            format!("self.{}.iter_living(alloc)", a)
+        } else {
+            format!("alloc.ids().zip(self.{}.iter())", a)
+        };
+
+        Function::new(&format!("iter_{}_with_{}", a, reference.field))
+            .with_generics(Generics::one("'a"))
+            .with_parameters(&format!("&'a self, alloc: &'a {}", self.get_allocator(&arena.name)))
+            .with_return(format!(
+                "impl Iterator<Item = ({}, {}<'a>, {})> + 'a",
+                self.get_valid_id_with_lifetime(&arena.name),
+                Self::get_row_view_type(&arena.name, "Ref"),
+                target_type,
+            ))
+            .add_line(CodeLine::new(0, &rows))
+            .add_line(CodeLine::new(1, ".map(move |(id, row)| {"))
+            .add_line(CodeLine::new(2, &format!("let target = {};", target)))
+            .add_line(CodeLine::new(2, "(id, row, target)"))
+            .add_line(CodeLine::new(1, "})"))
+    }
+
     fn generate_entity_child_link_functions(&self) -> impl Iterator<Item=Function> + '_ {
         self.entities
             .iter()
@@ -505,72 +1736,831 @@ impl World {
             })
     }
 
-    fn generate_entity_enum_link_functions(&self) -> impl Iterator<Item=Function> + '_ {
+    fn generate_entity_collection_link_functions(&self) -> impl Iterator<Item=Function> + '_ {
         self.entities
             .iter()
-            .flat_map(move |e| {
-                e.enums.iter().flat_map(move |ent_enum| {
-                    ent_enum.options.iter().map(move |c| (e, ent_enum, c))
-                })
-            })
-            .map(move |(e, ent_enum, c)| {
+            .flat_map(|e| e.collections.iter().map(move |c| (e, c)))
+            .map(move |(e, c)| {
                 let parent = e.base.as_field_name();
                 let child = c.as_field_name();
-
-                Function::new(&format!("link_{}_to_{}", &parent, &child))
-                    .with_parameters(&format!(
-                        "&mut self, {p}: &{p_id}, {c}: &{c_id}",
-                        p=&parent,
-                        p_id=self.get_valid_id(&e.base),
-                        c=&child,
-                        c_id=self.get_valid_id(c),
-                    ))
+                let parameters = format!(
+                    "&mut self, {p}: &{p_id}, {c}: &{c_id}",
+                    p=&parent,
+                    p_id=self.get_valid_id(&e.base),
+                    c=&child,
+                    c_id=self.get_valid_id(c),
+                );
+
+                let add = Function::new(&format!("add_{}_to_{}", &child, &parent))
+                    .with_parameters(&parameters)
                     .add_line(CodeLine::new(0, &format!(
-                        "self.{p}.{e}.insert({p}, {c}.id().into());",
+                        "self.{p}.{c}[{p}].push({c}.id());",
                         p=&parent,
-                        e=ent_enum.name.into_snake_case(),
                         c=&child,
                     )))
                     .add_line(CodeLine::new(0, &format!(
                         "self.{c}.{p}.insert({c}, {p}.id());",
                         p=&parent,
                         c=&child,
-                    )))
+                    )));
+
+                add
             })
     }
 
-    pub(crate) fn generate_arena_row(&self, arena: &ArenaCore) -> Struct {
-        let component_fields = arena
-            .components
+    fn generate_reference_index_functions(&self) -> impl Iterator<Item=Function> + '_ {
+        self.arenas
             .iter()
-            .filter(|c| c.source == Source::ByValue)
-            .filter_map(|c| c.get_row_field());
+            .filter(|a| !a.refs.is_empty())
+            .flat_map(move |a| {
+                let arena = a.name.as_field_name();
+                let parameters = format!("&mut self, id: &{}", self.get_valid_id(&a.name));
+
+                let index = a.refs.iter()
+                    .fold(Function::new(&format!("index_{}", arena)).with_parameters(&parameters), |func, r| {
+                        self.get_reference_lines(a, r, func, |target| {
+                            format!("{}.push(id.id());", target)
+                        })
+                    });
+
+                let unindex = a.refs.iter()
+                    .fold(Function::new(&format!("unindex_{}", arena)).with_parameters(&parameters), |func, r| {
+                        self.get_reference_lines(a, r, func, |target| {
+                            format!("{}.retain(|r| *r != id.id());", target)
+                        })
+                    });
+
+                let setters = a.refs.iter().flat_map(move |r| {
+                    let late = Self::is_late_reference(a, r).then(|| self.generate_late_reference_functions(a, r));
+                    std::iter::once(self.generate_reference_setter_function(a, r))
+                        .chain(late.into_iter().flatten())
+                });
 
-        let fields = component_fields.collect();
+                let queries = a.refs.iter().map(move |r| {
+                    Function::new(&format!("get_{}_by_{}", arena, r.field))
+                        .with_parameters(&format!("&self, id: &{}", self.get_valid_id(&r.arena)))
+                        .with_return(format!("&[{}]", self.get_id(&a.name)))
+                        .add_line(CodeLine::new(0, &format!(
+                            "&self.{t}.{i}[id]",
+                            t=r.arena.as_field_name(),
+                            i=Self::get_reverse_index_field(&a.name, r),
+                        )))
+                });
 
-        Struct::new(&format!("{}Row", arena.name))
-            .with_derives(Derives::with_debug_clone())
-            .with_fields(fields)
+                vec![index, unindex]
+                    .into_iter()
+                    .chain(setters)
+                    .chain(queries)
+            })
+    }
+
+    fn generate_many_reference_functions(&self) -> impl Iterator<Item=Function> + '_ {
+        self.arenas
+            .iter()
+            .flat_map(|a| a.many_refs.iter().map(move |(f, t)| (a, f, t)))
+            .flat_map(move |(a, field, target)| {
+                let arena = a.name.as_field_name();
+                let t = target.as_field_name();
+                let reverse = Self::get_reverse_field(&a.name, field, target);
+                let parameters = format!(
+                    "&mut self, id: &{}, target: &{}",
+                    self.get_valid_id(&a.name),
+                    self.get_valid_id(target),
+                );
+
+                let link = Function::new(&format!("link_{}_{}", arena, field))
+                    .with_parameters(&parameters)
+                    .add_line(CodeLine::new(0, &format!("if !self.{}.{}[id].contains(&target.id()) {{", arena, field)))
+                    .add_line(CodeLine::new(1, &format!("self.{}.{}[id].push(target.id());", arena, field)))
+                    .add_line(CodeLine::new(1, &format!("self.{}.{}[target].push(id.id());", t, reverse)))
+                    .add_line(CodeLine::new(0, "}"));
+
+                let unlink = Function::new(&format!("unlink_{}_{}", arena, field))
+                    .with_parameters(&parameters)
+                    .add_line(CodeLine::new(0, &format!("self.{}.{}[id].retain(|r| *r != target.id());", arena, field)))
+                    .add_line(CodeLine::new(0, &format!("self.{}.{}[target].retain(|r| *r != id.id());", t, reverse)));
+
+                let iter = Function::new(&format!("iter_{}_{}", arena, field))
+                    .with_parameters(&format!("&self, id: &{}", self.get_valid_id(&a.name)))
+                    .with_return(format!("impl Iterator<Item = {}> + '_", self.get_id(target)))
+                    .add_line(CodeLine::new(0, &format!("self.{}.{}[id].iter().copied()", arena, field)));
+
+                let iter_reverse = Function::new(&format!("iter_{}_{}", t, reverse))
+                    .with_parameters(&format!("&self, id: &{}", self.get_valid_id(target)))
+                    .with_return(format!("impl Iterator<Item = {}> + '_", self.get_id(&a.name)))
+                    .add_line(CodeLine::new(0, &format!("self.{}.{}[id].iter().copied()", t, reverse)));
+
+                vec![link, unlink, iter, iter_reverse]
+            })
+    }
+
+    // drops a deleted row from every link table it takes part in
+    fn generate_many_reference_prune_functions(&self) -> impl Iterator<Item=Function> + '_ {
+        self.arenas
+            .iter()
+            .filter(move |a| self.deletable_arenas.contains(&a.name) && self.has_many_references(&a.name))
+            .map(move |a| {
+                let arena = a.name.as_field_name();
+                let func = Function::new(&format!("prune_{}_links", arena))
+                    .with_parameters(&format!("&mut self, id: &{}", self.get_valid_id(&a.name)));
+
+                let func = a.many_refs.iter().fold(func, |func, (field, target)| {
+                    func.add_line(CodeLine::new(0, &format!("for target in self.{}.{}[id].iter() {{", arena, field)))
+                        .add_line(CodeLine::new(1, &format!(
+                            "self.{t}.{r}[{id}].retain(|r| *r != id.id());",
+                            t=target.as_field_name(),
+                            r=Self::get_reverse_field(&a.name, field, target),
+                            id=self.get_valid_id_expr(target, "*target"),
+                        )))
+                        .add_line(CodeLine::new(0, "}"))
+                });
+
+                self.get_incoming_many_references(&a.name).fold(func, |func, (referrer, field)| {
+                    func.add_line(CodeLine::new(0, &format!(
+                        "for referrer in self.{}.{}[id].iter() {{",
+                        arena,
+                        Self::get_reverse_field(&referrer.name, field, &a.name),
+                    )))
+                        .add_line(CodeLine::new(1, &format!(
+                            "self.{r}.{f}[{id}].retain(|r| *r != id.id());",
+                            r=referrer.name.as_field_name(),
+                            f=field,
+                            id=self.get_valid_id_expr(&referrer.name, "*referrer"),
+                        )))
+                        .add_line(CodeLine::new(0, "}"))
+                })
+            })
+    }
+
+    // moves the referrer between reverse indexes
+    fn generate_reference_setter_function(&self, referrer: &ArenaCore, reference: &Reference) -> Function {
+        let a = referrer.name.as_field_name();
+        let value = if reference.optional {
+            format!("Option<{}>", self.get_id(&reference.arena))
+        } else {
+            self.get_id(&reference.arena).to_string()
+        };
+
+        let func = Function::new(&Self::get_reference_setter_name(referrer, reference))
+            .with_parameters(&format!("&mut self, id: &{}, value: {}", self.get_valid_id(&referrer.name), value));
+
+        let hierarchy = Self::is_hierarchy(referrer, reference);
+
+        let func = if hierarchy {
+            func.with_return(format!("Result<(), {}>", CYCLE_ERROR))
+                .add_line(CodeLine::new(0, "if let Some(parent) = value {"))
+                .add_line(CodeLine::new(1, &format!(
+                    "if parent == id.id() || self.iter_{a}_ancestors_by_{f}({p}).any(|a| a == id.id()) {{",
+                    a=a,
+                    f=reference.field,
+                    p=self.get_ref_id_expr(&referrer.name, "parent"),
+                )))
+                .add_line(CodeLine::new(2, &format!(
+                    "return Err({}::new(\"{}\", \"{}\"));",
+                    CYCLE_ERROR,
+                    referrer.name,
+                    reference.field,
+                )))
+                .add_line(CodeLine::new(1, "}"))
+                .add_line(CodeLine::new(0, "}\n"))
+        } else {
+            func
+        };
+
+        let func = self.get_reference_lines(referrer, reference, func, |target| {
+            format!("{}.retain(|r| *r != id.id());", target)
+        });
+
+        let func = func.add_line(CodeLine::new(0, &format!("self.{}.{}[id] = value;", a, reference.field)));
+
+        let func = self.get_reference_lines(referrer, reference, func, |target| {
+            format!("{}.push(id.id());", target)
+        });
+
+        if hierarchy {
+            func.add_line(CodeLine::new(0, "Ok(())"))
+        } else {
+            func
+        }
+    }
+
+    // late references get typed set/clear wrappers, so the index-syncing setter takes another name
+    fn get_reference_setter_name(referrer: &ArenaCore, reference: &Reference) -> String {
+        let prefix = if Self::is_late_reference(referrer, reference) { "update" } else { "set" };
+        format!("{}_{}_{}", prefix, referrer.name.as_field_name(), reference.field)
+    }
+
+    fn generate_late_reference_functions(&self, referrer: &ArenaCore, reference: &Reference) -> Vec<Function> {
+        let a = referrer.name.as_field_name();
+        let valid_id = self.get_valid_id(&referrer.name);
+        let update = Self::get_reference_setter_name(referrer, reference);
+
+        let set = Function::new(&format!("set_{}_{}", a, reference.field))
+            .with_parameters(&format!("&mut self, id: &{}, value: {}", valid_id, self.get_id(&reference.arena)));
+
+        let clear = Function::new(&format!("clear_{}_{}", a, reference.field))
+            .with_parameters(&format!("&mut self, id: &{}", valid_id));
+
+        let (set, clear) = if Self::is_hierarchy(referrer, reference) {
+            let result = format!("Result<(), {}>", CYCLE_ERROR);
+            (set.with_return(result.clone()), clear.with_return(result))
+        } else {
+            (set, clear)
+        };
+
+        vec![
+            set.add_line(CodeLine::new(0, &format!("self.{}(id, Some(value))", update))),
+            clear.add_line(CodeLine::new(0, &format!("self.{}(id, None)", update))),
+        ]
+    }
+
+    // optional self links form a tree that can be walked in both directions
+    fn generate_hierarchy_functions(&self) -> impl Iterator<Item=Function> + '_ {
+        self.arenas
+            .iter()
+            .flat_map(|a| a.refs.iter().map(move |r| (a, r)))
+            .filter(|(a, r)| Self::is_hierarchy(a, r))
+            .flat_map(move |(arena, r)| {
+                let a = arena.name.as_field_name();
+                let f = &r.field;
+                let children = Self::get_reverse_index_field(&arena.name, r);
+                let id = self.get_id(&arena.name);
+
+                // the links are pub and can be written around the setter's cycle check,
+                // so both walks stop after visiting as many rows as the arena has slots
+                let ancestors = Function::new(&format!("iter_{}_ancestors_by_{}", a, f))
+                    .with_parameters(&format!("&self, id: &{}", self.get_valid_id(&arena.name)))
+                    .with_return(format!("impl Iterator<Item = {}> + '_", id))
+                    .add_line(CodeLine::new(0, &format!(
+                        "std::iter::successors(self.{a}.{f}[id], move |id| self.{a}.{f}[{id}])",
+                        a=a,
+                        f=f,
+                        id=if self.deletable_arenas.contains(&arena.name) {
+                            self.get_valid_id_expr(&arena.name, "*id")
+                        } else {
+                            "id".to_string()
+                        },
+                    )))
+                    .add_line(CodeLine::new(1, &format!(".take(self.{}.{}.len())", a, f)));
+
+                let hierarchy = Function::new(&format!("iter_{}_hierarchy_by_{}", a, f))
+                    .with_generics(Generics::one("'a"))
+                    .with_parameters(&format!("&'a self, alloc: &'a {}", self.get_allocator(&arena.name)))
+                    .with_return(format!("impl Iterator<Item = {}> + 'a", id))
+                    .add_line(CodeLine::new(0, "let mut queue = alloc"))
+                    .add_line(CodeLine::new(1, ".ids()"))
+                    .add_line(CodeLine::new(1, &format!(".filter(|id| self.{}.{}[id].is_none())", a, f)))
+                    .add_line(CodeLine::new(1, ".map(|id| id.id())"))
+                    .add_line(CodeLine::new(1, ".collect::<std::collections::VecDeque<_>>();\n"))
+                    .add_line(CodeLine::new(0, "std::iter::from_fn(move || {"))
+                    .add_line(CodeLine::new(1, "let id = queue.pop_front()?;"))
+                    .add_line(CodeLine::new(1, &format!(
+                        "queue.extend(self.{}.{}[{}].iter().copied());",
+                        a,
+                        children,
+                        self.get_ref_id_expr(&arena.name, "id"),
+                    )))
+                    .add_line(CodeLine::new(1, "Some(id)"))
+                    .add_line(CodeLine::new(0, "})"))
+                    .add_line(CodeLine::new(0, &format!(".take(self.{}.{}.len())", a, f)));
+
+                vec![ancestors, hierarchy]
+            })
+    }
+
+    fn generate_derived_update_functions(&self) -> impl Iterator<Item=Function> + '_ {
+        self.arenas
+            .iter()
+            .filter(|a| Self::get_derived_components(a).next().is_some())
+            .flat_map(move |arena| {
+                let a = arena.name.as_field_name();
+                let by_id = self.is_derived_update_by_id(arena);
+
+                let (parameters, arguments) = if by_id {
+                    (format!("&mut self, alloc: &{}", self.get_allocator(&arena.name)), "alloc")
+                } else {
+                    ("&mut self".to_string(), "")
+                };
+
+                let updates: Vec<Function> = Self::get_derived_components(arena)
+                    .map(|(c, function, inputs)| {
+                        let func = Function::new(&format!("update_{}_{}", a, c.field_name)).with_parameters(&parameters);
+
+                        if by_id {
+                            self.get_derived_update_by_id(arena, c, function, inputs, func)
+                        } else {
+                            Self::get_derived_update_by_zip(arena, c, function, inputs, func)
+                        }
+                    })
+                    .collect();
+
+                // declaration order is dependency order
+                let update_all = Self::get_derived_components(arena).fold(
+                    Function::new(&format!("update_{}", a)).with_parameters(&parameters),
+                    |func, (c, _, _)| func.add_line(CodeLine::new(0, &format!("self.update_{}_{}({});", a, c.field_name, arguments))),
+                );
+
+                updates.into_iter().chain(std::iter::once(update_all))
+            })
+    }
+
+    fn get_derived_update_by_zip(arena: &ArenaCore, comp: &Component, function: &str, inputs: &[DerivedInput], func: Function) -> Function {
+        let a = arena.name.as_field_name();
+
+        let func = func
+            .add_line(CodeLine::new(0, &format!("let {a} = &mut self.{a};", a=a)))
+            .add_line(CodeLine::new(0, &format!("{}.{}", a, comp.field_name)))
+            .add_line(CodeLine::new(1, ".iter_mut()"));
+
+        let func = inputs.iter().fold(func, |func, i| {
+            func.add_line(CodeLine::new(1, &format!(".zip({}.{}.iter())", a, i.field)))
+        });
+
+        let pattern = inputs
+            .iter()
+            .fold("value".to_string(), |pattern, i| format!("({}, {})", pattern, i.field));
+
+        func.add_line(CodeLine::new(1, &format!(
+            ".for_each(|{}| *value = {}({}));",
+            pattern,
+            function,
+            inputs.iter().map(|i| i.field.to_string()).collect::<Vec<_>>().join(", "),
+        )))
+    }
+
+    // linked inputs are looked up row by row, following a hierarchy from its roots if there is one
+    fn get_derived_update_by_id(&self, arena: &ArenaCore, comp: &Component, function: &str, inputs: &[DerivedInput], func: Function) -> Function {
+        let a = arena.name.as_field_name();
+
+        let func = match Self::get_derived_hierarchy(arena, inputs) {
+            Some(link) => func
+                .add_line(CodeLine::new(0, &format!("let ids = self.iter_{}_hierarchy_by_{}(alloc).collect::<Vec<_>>();", a, link)))
+                .add_line(CodeLine::new(0, "for id in ids {"))
+                .add_line(CodeLine::new(1, &format!("let id = {};", self.get_ref_id_expr(&arena.name, "id")))),
+            None => func
+                .add_line(CodeLine::new(0, "for id in alloc.ids() {"))
+                .add_line(CodeLine::new(1, "let id = &id;")),
+        };
+
+        let arguments = inputs
+            .iter()
+            .filter_map(|i| self.get_derived_argument(arena, i))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        func.add_line(CodeLine::new(1, &format!("let value = {}({});", function, arguments)))
+            .add_line(CodeLine::new(1, &format!("self.{}.{}[id] = value;", a, comp.field_name)))
+            .add_line(CodeLine::new(0, "}"))
+    }
+
+    fn get_derived_argument(&self, arena: &ArenaCore, input: &DerivedInput) -> Option<String> {
+        let a = arena.name.as_field_name();
+
+        let link = match &input.link {
+            Some(link) => link,
+            None => return Some(format!("&self.{}.{}[id]", a, input.field)),
+        };
+
+        let (target, optional) = self.get_link_target(arena, link)?;
+        let t = target.as_field_name();
+
+        if optional {
+            Some(format!(
+                "self.{a}.{l}[id].map(|link| &self.{t}.{f}[{id}])",
+                a=a,
+                l=link,
+                t=t,
+                f=input.field,
+                id=self.get_ref_id_expr(target, "link"),
+            ))
+        } else {
+            Some(format!(
+                "&self.{t}.{f}[{id}]",
+                t=t,
+                f=input.field,
+                id=self.get_ref_id_expr(target, &format!("self.{}.{}[id]", a, link)),
+            ))
+        }
+    }
+
+    // transient arenas skip dead slots and linked inputs are read by id, so both walk the allocator
+    fn is_derived_update_by_id(&self, arena: &ArenaCore) -> bool {
+        self.deletable_arenas.contains(&arena.name) || Self::get_derived_components(arena).any(|(_, _, inputs)| Self::has_linked_inputs(inputs))
+    }
+
+    fn has_linked_inputs(inputs: &[DerivedInput]) -> bool {
+        inputs.iter().any(|i| i.link.is_some())
+    }
+
+    fn get_derived_hierarchy<'a>(arena: &'a ArenaCore, inputs: &'a [DerivedInput]) -> Option<&'a SnakeCase> {
+        inputs
+            .iter()
+            .filter_map(|i| i.link.as_ref())
+            .find(|link| arena.refs.iter().any(|r| r.field == **link && Self::is_hierarchy(arena, r)))
+    }
+
+    fn generate_update_derived_function(&self) -> Option<Function> {
+        let order = self.get_derived_update_order().unwrap_or_default();
+
+        if order.is_empty() {
+            return None;
+        }
+
+        let func = order.iter().fold(Function::new("update_derived").with_parameters("&mut self"), |func, arena| {
+            let a = arena.name.as_field_name();
+            let alloc = if self.is_derived_update_by_id(arena) {
+                format!("&self.allocators.{}", a)
+            } else {
+                String::new()
+            };
+            func.add_line(CodeLine::new(0, &format!("self.state.update_{}({});", a, alloc)))
+        });
+
+        Some(func)
+    }
+
+    fn get_derived_components(arena: &ArenaCore) -> impl Iterator<Item = (&Component, &String, &Vec<DerivedInput>)> {
+        arena.components.iter().filter_map(|c| match &c.source {
+            Source::Derived { function, inputs } => Some((c, function, inputs)),
+            _ => None,
+        })
+    }
+
+    fn is_hierarchy(arena: &ArenaCore, reference: &Reference) -> bool {
+        reference.optional && reference.arena == arena.name
+    }
+
+    // late references start out empty rather than being passed in the row
+    fn is_late_reference(arena: &ArenaCore, reference: &Reference) -> bool {
+        arena
+            .components
+            .iter()
+            .any(|c| c.field_name == reference.field && c.source == Source::ByDefault)
+    }
+
+    // borrows a local id so it can index a component
+    fn get_ref_id_expr(&self, arena: &ArenaName, id: &str) -> String {
+        if self.deletable_arenas.contains(arena) {
+            self.get_valid_id_expr(arena, id)
+        } else {
+            format!("&{}", id)
+        }
+    }
+
+    fn get_reference_lines(
+        &self,
+        referrer: &ArenaCore,
+        reference: &Reference,
+        func: Function,
+        action: impl Fn(&str) -> String,
+    ) -> Function {
+        let target = format!(
+            "self.{t}.{i}[{id}]",
+            t=reference.arena.as_field_name(),
+            i=Self::get_reverse_index_field(&referrer.name, reference),
+            id=self.get_valid_id_expr(&reference.arena, &reference.field.to_string()),
+        );
+
+        if reference.optional {
+            func.add_line(CodeLine::new(0, &format!(
+                "if let Some({f}) = self.{a}.{f}[id] {{",
+                a=referrer.name.as_field_name(),
+                f=reference.field,
+            )))
+                .add_line(CodeLine::new(1, &action(&target)))
+                .add_line(CodeLine::new(0, "}"))
+        } else {
+            func.add_line(CodeLine::new(0, &format!(
+                "let {f} = self.{a}.{f}[id];",
+                a=referrer.name.as_field_name(),
+                f=reference.field,
+            )))
+                .add_line(CodeLine::new(0, &action(&target)))
+        }
+    }
+
+    fn generate_delete_check_functions(&self) -> impl Iterator<Item=Function> + '_ {
+        self.arenas
+            .iter()
+            .filter(move |a| self.deletable_arenas.contains(&a.name) && self.needs_delete_check(&a.name, &mut vec![]))
+            .map(move |a| {
+                let arena = a.name.as_field_name();
+                let func = Function::new(&format!("can_delete_{}", arena))
+                    .with_parameters(&format!("&self, id: &{}", self.get_valid_id(&a.name)))
+                    .with_return(format!("Result<(), {}>", DELETE_ERROR));
+
+                let func = self.get_incoming_references(&a.name)
+                    .fold(func, |func, (referrer, r)| {
+                        let index = Self::get_reverse_index_field(&referrer.name, r);
+                        match r.on_delete {
+                            ReferentialAction::Nullify => func,
+                            ReferentialAction::Restrict => func
+                                .add_line(CodeLine::new(0, &format!("if !self.{a}.{i}[id].is_empty() {{", a=arena, i=index)))
+                                .add_line(CodeLine::new(1, &format!(
+                                    "return Err({}::new(\"{}\", \"{}\"));",
+                                    DELETE_ERROR,
+                                    referrer.name,
+                                    r.field,
+                                )))
+                                .add_line(CodeLine::new(0, "}\n")),
+                            ReferentialAction::Cascade if self.needs_delete_check(&referrer.name, &mut vec![a.name.clone()]) => func
+                                .add_line(CodeLine::new(0, &format!("for referrer in self.{a}.{i}[id].iter() {{", a=arena, i=index)))
+                                .add_line(CodeLine::new(1, &format!(
+                                    "self.can_delete_{}(&Valid::new(*referrer))?;",
+                                    referrer.name.as_field_name(),
+                                )))
+                                .add_line(CodeLine::new(0, "}\n")),
+                            ReferentialAction::Cascade => func,
+                        }
+                    });
+
+                self.get_child_delete_checks(func, a)
+                    .add_line(CodeLine::new(0, "Ok(())"))
+            })
+    }
+
+    // owned children are deleted along with their parent, so their restrictions apply to it
+    fn get_child_delete_checks(&self, func: Function, arena: &ArenaCore) -> Function {
+        let entity = match self.get_entity(&arena.name) {
+            Some(entity) => entity,
+            None => return func,
+        };
+
+        let a = arena.name.as_field_name();
+        let needs_check = |c: &ArenaName| {
+            self.deletable_arenas.contains(c) && self.needs_delete_check(c, &mut vec![arena.name.clone()])
+        };
+
+        let func = entity.children.iter().filter(|c| needs_check(c)).fold(func, |func, child| {
+            let c = child.as_field_name();
+            func.add_line(CodeLine::new(0, &format!("if let Some(child) = self.{a}.{c}[id] {{", a=a, c=c)))
+                .add_line(CodeLine::new(1, &format!("self.can_delete_{}(&Valid::new(child))?;", c)))
+                .add_line(CodeLine::new(0, "}\n"))
+        });
+
+        let func = entity.collections.iter().filter(|c| needs_check(c)).fold(func, |func, child| {
+            let c = child.as_field_name();
+            func.add_line(CodeLine::new(0, &format!("for child in self.{a}.{c}[id].iter() {{", a=a, c=c)))
+                .add_line(CodeLine::new(1, &format!("self.can_delete_{}(&Valid::new(*child))?;", c)))
+                .add_line(CodeLine::new(0, "}\n"))
+        });
+
+        entity.enums.iter().fold(func, |func, entity_enum| {
+            let options: Vec<_> = entity_enum.options.iter().filter(|c| needs_check(c)).collect();
+            if options.is_empty() {
+                return func;
+            }
+
+            let func = func.add_line(CodeLine::new(0, &format!(
+                "match self.{a}.{ee}[id] {{",
+                a=a,
+                ee=entity_enum.name.into_snake_case(),
+            )));
+
+            let func = options.iter().fold(func, |func, opt| {
+                func.add_line(CodeLine::new(1, &format!(
+                    "{}::{}(child) => self.can_delete_{}(&Valid::new(child))?,",
+                    entity_enum.name,
+                    opt,
+                    opt.as_field_name(),
+                )))
+            });
+
+            if options.len() < entity_enum.options.len() {
+                func.add_line(CodeLine::new(1, "_ => {},"))
+            } else {
+                func
+            }
+            .add_line(CodeLine::new(0, "}\n"))
+        })
+    }
+
+    fn generate_entity_enum_link_functions(&self) -> impl Iterator<Item=Function> + '_ {
+        self.entities
+            .iter()
+            .flat_map(move |e| {
+                e.enums.iter().flat_map(move |ent_enum| {
+                    ent_enum.options.iter().map(move |c| (e, ent_enum, c))
+                })
+            })
+            .map(move |(e, ent_enum, c)| {
+                let parent = e.base.as_field_name();
+                let child = c.as_field_name();
+
+                Function::new(&format!("link_{}_to_{}", &parent, &child))
+                    .with_parameters(&format!(
+                        "&mut self, {p}: &{p_id}, {c}: &{c_id}",
+                        p=&parent,
+                        p_id=self.get_valid_id(&e.base),
+                        c=&child,
+                        c_id=self.get_valid_id(c),
+                    ))
+                    .add_line(CodeLine::new(0, &format!(
+                        "self.{p}.{e}.insert({p}, {c}.id().into());",
+                        p=&parent,
+                        e=ent_enum.name.into_snake_case(),
+                        c=&child,
+                    )))
+                    .add_line(CodeLine::new(0, &format!(
+                        "self.{c}.{p}.insert({c}, {p}.id());",
+                        p=&parent,
+                        c=&child,
+                    )))
+            })
+    }
+
+    pub(crate) fn generate_arena_row(&self, arena: &ArenaCore) -> Struct {
+        let component_fields = arena
+            .components
+            .iter()
+            .filter(|c| c.source == Source::ByValue)
+            .filter_map(|c| c.get_row_field());
+
+        let fields = component_fields.collect();
+
+        Struct::new(&format!("{}Row", arena.name))
+            .with_derives(Derives::with_debug_clone())
+            .with_fields(fields)
+    }
+
+    pub fn generate_arena_row_views(&self) -> Vec<RowView> {
+        self.arenas
+            .iter()
+            .filter(|a| Self::get_view_components(a).next().is_some())
+            .flat_map(|a| {
+                let view_ref = RowView::new(Self::get_row_view_type(&a.name, "Ref"), false, Self::get_view_components(a));
+
+                let mut_components = Self::get_mut_view_components(a).collect::<Vec<_>>();
+                let view_mut = if mut_components.is_empty() {
+                    None
+                } else {
+                    Some(RowView::new(Self::get_row_view_type(&a.name, "Mut"), true, mut_components))
+                };
+
+                std::iter::once(view_ref).chain(view_mut)
+            })
+            .collect()
+    }
+
+    // rare components are keyed by id so they cannot be zipped into row views or joins
+    fn get_view_components(arena: &ArenaCore) -> impl Iterator<Item = &Component> {
+        arena.components.iter().filter(|c| c.density != Density::Rare)
+    }
+
+    // derived components are only written by their update functions
+    fn get_mut_view_components(arena: &ArenaCore) -> impl Iterator<Item = &Component> {
+        Self::get_view_components(arena).filter(|c| !matches!(c.source, Source::Derived { .. }))
+    }
+
+    fn get_row_view_type(arena: &ArenaName, suffix: &str) -> String {
+        format!("{}{}", arena, suffix)
     }
 
     fn generate_arena_impl(&self, arena: &ArenaCore) -> Impl {
-        Impl::from(&Type::new(arena.name.as_str()))
+        let arena_impl = Impl::from(&Type::new(arena.name.as_str()))
             .add_function(self.get_insert_function(arena))
-            .add_function(self.get_create_function(arena))
+            .add_function(self.get_create_function(arena));
+
+        let arena_impl = match self.get_remove_function(arena) {
+            Some(remove) => arena_impl.add_function(remove),
+            None => arena_impl,
+        };
+
+        self.get_row_view_functions(arena)
+            .into_iter()
+            .fold(arena_impl, |arena_impl, f| arena_impl.add_function(f))
+    }
+
+    fn get_row_view_functions(&self, arena: &ArenaCore) -> Vec<Function> {
+        if Self::get_view_components(arena).next().is_none() {
+            return vec![];
+        }
+
+        let view_ref = Self::get_row_view_type(&arena.name, "Ref");
+        let view_mut = Self::get_row_view_type(&arena.name, "Mut");
+        let fields: Vec<String> = Self::get_view_components(arena).map(|c| c.field_name.to_string()).collect();
+        let mut_fields: Vec<String> = Self::get_mut_view_components(arena).map(|c| c.field_name.to_string()).collect();
+
+        let get = fields.iter().fold(
+            Function::new("get")
+                .with_parameters(&format!("&self, id: &{}", self.get_valid_id(&arena.name)))
+                .with_return(view_ref.clone())
+                .add_line(CodeLine::new(0, &format!("{} {{", view_ref))),
+            |func, f| func.add_line(CodeLine::new(1, &format!("{f}: &self.{f}[id],", f=f))),
+        ).add_line(CodeLine::new(0, "}"));
+
+        // transient arenas keep the slots of dead ids, so they are only iterated alongside the allocator
+        let deletable = self.deletable_arenas.contains(&arena.name);
+
+        let iter = if deletable {
+            self.get_iter_living_function(arena, "iter", "&'a self", &view_ref, &fields)
+        } else {
+            Self::get_zipped_iter_function("iter", "&self", &view_ref, &fields)
+        };
+
+        let mut functions = vec![get, iter];
+
+        // derived components are left out, so an arena of only derived components has no mutable view
+        if !mut_fields.is_empty() {
+            let get_mut = mut_fields.iter().fold(
+                Function::new("get_mut")
+                    .with_parameters(&format!("&mut self, id: &{}", self.get_valid_id(&arena.name)))
+                    .with_return(view_mut.clone())
+                    .add_line(CodeLine::new(0, &format!("{} {{", view_mut))),
+                |func, f| func.add_line(CodeLine::new(1, &format!("{f}: &mut self.{f}[id],", f=f))),
+            ).add_line(CodeLine::new(0, "}"));
+
+            let iter_mut = if deletable {
+                self.get_iter_living_function(arena, "iter_mut", "&'a mut self", &view_mut, &mut_fields)
+            } else {
+                Self::get_zipped_iter_function("iter_mut", "&mut self", &view_mut, &mut_fields)
+            };
+
+            functions.push(get_mut);
+            functions.push(iter_mut);
+        }
+
+        functions
+    }
+
+    fn get_iter_living_function(&self, arena: &ArenaCore, iter: &str, receiver: &str, view: &str, fields: &[String]) -> Function {
+        let name = iter.replacen("iter", "iter_living", 1);
+
+        let func = Function::new(&name)
+            .with_generics(Generics::one("'a"))
+            .with_parameters(&format!("{}, alloc: &'a {}", receiver, self.get_allocator(&arena.name)))
+            .with_return(format!(
+                "impl Iterator<Item = ({}, {}<'a>)> + 'a",
+                self.get_valid_id_with_lifetime(&arena.name),
+                view,
+            ));
+
+        Self::add_zipped_lines(func, iter, view, fields)
+            .add_line(CodeLine::new(1, ".enumerate()"))
+            .add_line(CodeLine::new(1, ".filter_map(move |(i, row)| alloc.validate_index(i).map(|id| (id, row)))"))
+    }
+
+    fn get_zipped_iter_function(name: &str, receiver: &str, view: &str, fields: &[String]) -> Function {
+        let func = Function::new(name)
+            .with_parameters(receiver)
+            .with_return(format!("impl Iterator<Item = {}> + '_", view));
+
+        Self::add_zipped_lines(func, name, view, fields)
+    }
+
+    fn add_zipped_lines(func: Function, iter: &str, view: &str, fields: &[String]) -> Function {
+        let func = func
+            .add_line(CodeLine::new(0, &format!("self.{}", fields[0])))
+            .add_line(CodeLine::new(1, &format!(".{}()", iter)));
+
+        let func = fields[1..].iter().fold(func, |func, f| {
+            func.add_line(CodeLine::new(1, &format!(".zip(self.{}.{}())", f, iter)))
+        });
+
+        let pattern = fields[1..]
+            .iter()
+            .fold(fields[0].clone(), |pattern, f| format!("({}, {})", pattern, f));
+
+        func.add_line(CodeLine::new(1, &format!(
+            ".map(|{}| {} {{ {} }})",
+            pattern,
+            view,
+            fields.join(", "),
+        )))
     }
 
     fn get_insert_function(&self, arena: &ArenaCore) -> Function {
-        let mut func = Function::new("insert").with_parameters(&format!(
+        let func = Function::new("insert").with_parameters(&format!(
             "&mut self, id: &{}, row: {}",
             self.get_valid_id(&arena.name),
             self.generate_arena_row(arena).typ,
         ));
 
+        let mut func = arena
+            .components
+            .iter()
+            .filter_map(|c| match &c.source {
+                Source::ByExpr(expr) => Some((c, expr)),
+                _ => None,
+            })
+            .fold(func, |func, (comp, expr)| {
+                func.add_line(CodeLine::new(
+                    0,
+                    &format!("self.{}.insert(id, {});", comp.field_name, expr),
+                ))
+            });
+
         for field in self.generate_arena_row(arena).fields {
-            func = func.add_line(CodeLine::new(
-                0,
-                &format!("self.{}.insert(id, row.{});", field.name, field.name),
-            ));
+            func = if arena.components.iter().any(|c| c.field_name == field.name && c.density == Density::Rare) {
+                func.add_line(CodeLine::new(0, &format!("if let Some({}) = row.{} {{", field.name, field.name)))
+                    .add_line(CodeLine::new(1, &format!("self.{}.insert(id, {});", field.name, field.name)))
+                    .add_line(CodeLine::new(0, "}"))
+            } else {
+                func.add_line(CodeLine::new(
+                    0,
+                    &format!("self.{}.insert(id, row.{});", field.name, field.name),
+                ))
+            };
         }
 
         let mut func = arena
@@ -584,13 +2574,33 @@ impl World {
                 ))
             });
 
-        for (field, _arena) in arena.optional_refs.iter() {
+        // inputs are declared first, so derived inputs are already computed,
+        // linked inputs are in other arenas and are read by update_derived
+        func = Self::get_derived_components(arena)
+            .fold(func, |func, (comp, function, inputs)| {
+                let value = if Self::has_linked_inputs(inputs) {
+                    "Default::default()".to_string()
+                } else {
+                    let args: Vec<String> = inputs.iter().map(|i| format!("&self.{}[id]", i.field)).collect();
+                    format!("{}({})", function, args.join(", "))
+                };
+                func.add_line(CodeLine::new(0, &format!("self.{}.insert(id, {});", comp.field_name, value)))
+            });
+
+        for (field, _arena) in arena.many_refs.iter() {
             func = func.add_line(CodeLine::new(
                 0,
-                &format!("self.{}.insert(id, None);", field),
+                &format!("self.{}.insert(id, Vec::new());", field),
             ));
         }
 
+        // a transient slot may have been killed while tagged
+        if self.deletable_arenas.contains(&arena.name) {
+            for field in arena.tags.iter() {
+                func = func.add_line(CodeLine::new(0, &format!("self.{}.unset(id);", field)));
+            }
+        }
+
         if let Some(entity) = self.get_entity(&arena.name) {
             for c in entity.children.iter() {
                 func = func.add_line(CodeLine::new(
@@ -598,9 +2608,85 @@ impl World {
                     &format!("self.{}.insert(id, None);", c.as_field_name()),
                 ));
             }
+
+            for c in entity.collections.iter() {
+                func = func.add_line(CodeLine::new(
+                    0,
+                    &format!("self.{}.insert(id, Vec::new());", c.as_field_name()),
+                ));
+            }
+        }
+
+        for (referrer, r) in self.get_incoming_references(&arena.name) {
+            func = func.add_line(CodeLine::new(
+                0,
+                &format!("self.{}.insert(id, Vec::new());", Self::get_reverse_index_field(&referrer.name, r)),
+            ));
+        }
+
+        for (referrer, field) in self.get_incoming_many_references(&arena.name) {
+            func = func.add_line(CodeLine::new(
+                0,
+                &format!("self.{}.insert(id, Vec::new());", Self::get_reverse_field(&referrer.name, field, &arena.name)),
+            ));
+        }
+
+        func
+    }
+
+    fn get_remove_function(&self, arena: &ArenaCore) -> Option<Function> {
+        if !self.deletable_arenas.contains(&arena.name) {
+            return None;
+        }
+
+        let mut lines: Vec<String> = arena
+            .components
+            .iter()
+            // dense values are overwritten on insert, only clearable columns are known to be Default
+            .filter(|c| c.density != Density::Dense || c.clearable)
+            .map(|comp| match comp.density {
+                Density::Dense => format!("self.{}.clear(id);", comp.field_name),
+                Density::Sparse => format!("self.{}.remove(id);", comp.field_name),
+                Density::Rare => format!("self.{}.remove(&id.id());", comp.field_name),
+            })
+            .collect();
+
+        for (field, _arena) in arena.many_refs.iter() {
+            lines.push(format!("self.{}.clear(id);", field));
+        }
+
+        for field in arena.tags.iter() {
+            lines.push(format!("self.{}.unset(id);", field));
+        }
+
+        if let Some(entity) = self.get_entity(&arena.name) {
+            for c in entity.children.iter() {
+                lines.push(format!("self.{}.remove(id);", c.as_field_name()));
+            }
+
+            for c in entity.collections.iter() {
+                lines.push(format!("self.{}.clear(id);", c.as_field_name()));
+            }
+        }
+
+        for (referrer, r) in self.get_incoming_references(&arena.name) {
+            lines.push(format!("self.{}.clear(id);", Self::get_reverse_index_field(&referrer.name, r)));
         }
 
-        func
+        for (referrer, field) in self.get_incoming_many_references(&arena.name) {
+            lines.push(format!("self.{}.clear(id);", Self::get_reverse_field(&referrer.name, field, &arena.name)));
+        }
+
+        let func = Function::new("remove").with_parameters(&format!(
+            "&mut self, {}: &{}",
+            if lines.is_empty() { "_id" } else { "id" },
+            self.get_valid_id(&arena.name),
+        ));
+
+        lines
+            .iter()
+            .fold(func, |func, line| func.add_line(CodeLine::new(0, line)))
+            .into()
     }
 
     fn get_create_function(&self, arena: &ArenaCore) -> Function {
@@ -617,8 +2703,94 @@ impl World {
             .add_line(CodeLine::new(0, "id"))
     }
 
+    pub fn has_delete_function(&self, arena: &ArenaName) -> bool {
+        if self.deletable_entities.contains(arena) {
+            return true;
+        }
+
+        self.deletable_arenas.contains(arena)
+            && !self.entities.iter().any(|e| e.owns_arena(arena))
+    }
+
+    pub fn get_incoming_references<'a>(
+        &'a self,
+        arena: &'a ArenaName,
+    ) -> impl Iterator<Item = (&'a ArenaCore, &'a Reference)> + 'a {
+        self.arenas
+            .iter()
+            .flat_map(|a| a.refs.iter().map(move |r| (a, r)))
+            .filter(move |(_, r)| r.arena == *arena)
+    }
+
+    pub fn get_incoming_many_references<'a>(
+        &'a self,
+        arena: &'a ArenaName,
+    ) -> impl Iterator<Item = (&'a ArenaCore, &'a SnakeCase)> + 'a {
+        self.arenas
+            .iter()
+            .flat_map(|a| a.many_refs.iter().map(move |(f, t)| (a, f, t)))
+            .filter(move |(_, _, t)| *t == arena)
+            .map(|(a, f, _)| (a, f))
+    }
+
+    fn get_reverse_index_field(referrer: &ArenaName, reference: &Reference) -> SnakeCase {
+        reference
+            .reverse_index
+            .clone()
+            .unwrap_or_else(|| Self::get_reverse_field(referrer, &reference.field, &reference.arena))
+    }
+
+    fn get_reverse_field(referrer: &ArenaName, field: &SnakeCase, target: &ArenaName) -> SnakeCase {
+        if *field == target.as_field_name() {
+            referrer.as_field_name()
+        } else {
+            SnakeCase::from_str(&format!("{}_{}", referrer.as_field_name(), field)).unwrap()
+        }
+    }
+
+    fn has_many_references(&self, arena: &ArenaName) -> bool {
+        !self.get_arena(arena).many_refs.is_empty()
+            || self.get_incoming_many_references(arena).next().is_some()
+    }
+
+    // permanent options are created with their entity and never replaced
+    fn has_transient_options(&self, entity_enum: &EntityEnumCore) -> bool {
+        entity_enum.options.iter().all(|o| self.deletable_arenas.contains(o))
+    }
+
+    fn needs_delete_check(&self, arena: &ArenaName, visited: &mut Vec<ArenaName>) -> bool {
+        visited.push(arena.clone());
+
+        let incoming: Vec<_> = self.get_incoming_references(arena).collect();
+
+        incoming.iter().any(|(_, r)| r.on_delete == ReferentialAction::Restrict)
+            || incoming.iter().any(|(referrer, r)| {
+                r.on_delete == ReferentialAction::Cascade
+                    && !visited.contains(&referrer.name)
+                    && self.needs_delete_check(&referrer.name, visited)
+            })
+            || self.get_owned_children(arena).iter().any(|c| {
+                !visited.contains(c) && self.needs_delete_check(c, visited)
+            })
+    }
+
+    fn get_valid_id_expr(&self, arena: &ArenaName, id: &str) -> String {
+        if self.deletable_arenas.contains(arena) {
+            format!("&Valid::new({})", id)
+        } else {
+            id.to_string()
+        }
+    }
+
     pub fn get_arena(&self, arena: &ArenaName) -> &ArenaCore {
-        self.arenas.iter().find(|a| a.name == *arena).unwrap()
+        self.try_get_arena(arena).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_arena(&self, arena: &ArenaName) -> std::result::Result<&ArenaCore, SchemaError> {
+        self.arenas
+            .iter()
+            .find(|a| a.name == *arena)
+            .ok_or_else(|| Self::unknown_arena(arena))
     }
 
     pub fn get_entity(&self, arena: &ArenaName) -> Option<&EntityCore> {
@@ -635,19 +2807,37 @@ impl World {
     }
 
     pub fn get_allocator(&self, arena: &ArenaName) -> Type {
-        self.allocator.get(arena).unwrap().clone()
+        self.try_get_allocator(arena).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_allocator(&self, arena: &ArenaName) -> std::result::Result<Type, SchemaError> {
+        self.allocator.get(arena).cloned().ok_or_else(|| Self::unknown_arena(arena))
     }
 
     pub fn get_id(&self, arena: &ArenaName) -> Type {
-        self.id.get(arena).unwrap().clone()
+        self.try_get_id(arena).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_id(&self, arena: &ArenaName) -> std::result::Result<Type, SchemaError> {
+        self.id.get(arena).cloned().ok_or_else(|| Self::unknown_arena(arena))
     }
 
     pub fn get_valid_id(&self, arena: &ArenaName) -> Type {
-        self.valid_id.get(arena).unwrap().clone()
+        self.try_get_valid_id(arena).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    pub fn try_get_valid_id(&self, arena: &ArenaName) -> std::result::Result<Type, SchemaError> {
+        self.valid_id.get(arena).cloned().ok_or_else(|| Self::unknown_arena(arena))
+    }
+
+    fn unknown_arena(arena: &ArenaName) -> SchemaError {
+        SchemaError::UnknownArena {
+            arena: arena.to_string(),
+        }
     }
 
     pub fn get_valid_id_with_lifetime(&self, arena: &ArenaName) -> Type {
-        let mut id = self.valid_id.get(arena).unwrap().clone();
+        let mut id = self.get_valid_id(arena);
         if id.name.as_str() == "Valid" {
             id.types.push_front("'a");
         }
@@ -658,10 +2848,13 @@ impl World {
 const WORLD: &'static str = "World";
 const ALLOCATORS: &'static str = "Allocators";
 const STATE: &'static str = "State";
+const DELETE_ERROR: &'static str = "DeleteRestricted";
+const CYCLE_ERROR: &'static str = "LinkCycle";
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::entities::EntityEnum;
 
     #[test]
     fn generate_world() {
@@ -737,6 +2930,765 @@ pub mod tests {
         world.insert(Arena::<Transient>::new("Test"));
     }
 
+    #[test]
+    fn entity_collections() {
+        let vessel = Arena::<Transient>::new("Vessel");
+        let cargo_pod = Arena::<Transient>::new("CargoPod");
+
+        let mut entity = Entity::new(&vessel);
+        entity.add_collection(&cargo_pod);
+
+        let mut world = World::new();
+        world.insert(vessel);
+        world.insert(cargo_pod);
+        world.insert(entity);
+
+        assert_eq!(get_type("Component<Self,Vec<GenId<CargoPod>>>"), get_field_type(&world, "Vessel", "cargo_pod"));
+        assert_eq!(get_type("Component<Self,GenId<Vessel>>"), get_field_type(&world, "CargoPod", "vessel"));
+
+        assert!(has_function(&world.generate_state_impl(), "add_cargo_pod_to_vessel"));
+        assert!(has_function(&world.generate_world_impl(), "remove_cargo_pod_from_vessel"));
+    }
+
+    #[test]
+    fn late_references() {
+        let body = Arena::<Permanent>::new("Body");
+        let mut vessel = Arena::<Transient>::new("Vessel");
+        vessel.add_late_reference_with_field("target", &body);
+
+        let mut world = World::new();
+        world.insert(body);
+        world.insert(vessel);
+
+        assert_eq!(get_type("Component<Self,Option<Id<Body>>>"), get_field_type(&world, "Vessel", "target"));
+        assert!(get_row_fields(&world, "Vessel").is_empty());
+
+        assert_eq!(get_type("Component<Self,Vec<GenId<Vessel>>>"), get_field_type(&world, "Body", "vessel_target"));
+
+        let state_impl = world.generate_state_impl();
+        assert!(has_function(&state_impl, "set_vessel_target"));
+        assert!(has_function(&state_impl, "clear_vessel_target"));
+        assert!(has_function(&state_impl, "get_vessel_by_target"));
+        assert!(state_impl.to_string().contains("value: Id<Body>"));
+    }
+
+    #[test]
+    fn delete_non_entity_arenas() {
+        let mut world = World::new();
+        world.insert(Arena::<Permanent>::new("System"));
+        world.insert(Arena::<Transient>::new("Nation"));
+
+        let world_impl = world.generate_world_impl();
+
+        assert!(has_function(&world_impl, "delete_nation"));
+        assert!(!has_function(&world_impl, "delete_system"));
+    }
+
+    #[test]
+    fn referential_actions() {
+        let nation = Arena::<Transient>::new("Nation");
+
+        let mut colony = Arena::<Transient>::new("Colony");
+        colony.add_optional_reference(&nation);
+        colony.set_referential_action("nation", ReferentialAction::Restrict);
+
+        let mut vessel = Arena::<Transient>::new("Vessel");
+        vessel.add_optional_reference_with_field("controller", &nation);
+
+        let mut world = World::new();
+        world.insert(nation);
+        world.insert(colony);
+        world.insert(vessel);
+
+        assert_eq!(get_type("Component<Self,Vec<GenId<Colony>>>"), get_field_type(&world, "Nation", "colony"));
+        assert_eq!(get_type("Component<Self,Vec<GenId<Vessel>>>"), get_field_type(&world, "Nation", "vessel_controller"));
+
+        let state = world.generate_state_impl();
+        assert!(has_function(&state, "can_delete_nation"));
+        assert!(!has_function(&state, "can_delete_vessel"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn nullify_required_reference() {
+        let nation = Arena::<Transient>::new("Nation");
+        let mut colony = Arena::<Transient>::new("Colony");
+        colony.add_owner_reference(&nation);
+        colony.set_referential_action("nation", ReferentialAction::Nullify);
+    }
+
+    #[test]
+    fn reference_matrix() {
+        let nation = Arena::<Transient>::new("Nation");
+        let body = Arena::<Permanent>::new("Body");
+
+        let mut colony = Arena::<Transient>::new("Colony");
+        colony.add_owner_reference(&nation);
+        colony.add_reference(&body);
+        assert_eq!(
+            Err(SchemaError::InvalidReference {
+                arena: "Colony".to_string(),
+                field: "capital".to_string(),
+                target: "Nation".to_string(),
+                reason: "must point at owner so that it can be deleted with it".to_string(),
+            }),
+            colony.try_add_reference_with_field("capital", &nation)
+        );
+
+        let mut system = Arena::<Permanent>::new("System");
+        assert_eq!(
+            Err(SchemaError::InvalidReference {
+                arena: "System".to_string(),
+                field: "nation".to_string(),
+                target: "Nation".to_string(),
+                reason: "cannot be unlinked if child removed".to_string(),
+            }),
+            system.try_add_reference(&nation)
+        );
+        system.add_optional_reference(&nation);
+        system.add_many_reference(&colony);
+
+        let mut vessel = Arena::<Transient>::new("Vessel");
+        vessel.add_owner_reference_with_field("controller", &nation);
+        vessel.set_referential_action("controller", ReferentialAction::Restrict);
+
+        let mut world = World::new();
+        world.insert(nation);
+        world.insert(body);
+        world.insert(colony);
+        world.insert(system);
+
+        let error = SchemaError::InvalidReference {
+            arena: "Vessel".to_string(),
+            field: "controller".to_string(),
+            target: "Nation".to_string(),
+            reason: "must point at owner so that it can be deleted with it".to_string(),
+        };
+        assert_eq!(Err(vec![error.clone()]), world.try_insert(vessel));
+        assert_eq!(Err(vec![error]), world.validate());
+
+        assert_eq!(None, get_invalid_relationship(false, true, Relationship::ManyOwns));
+        assert_eq!(
+            Some("child entity will leak if parent removed"),
+            get_invalid_relationship(true, false, Relationship::MaybeOwns)
+        );
+    }
+
+    #[test]
+    fn schema_errors() {
+        assert_eq!(
+            Err(SchemaError::InvalidArenaName { name: "body".to_string() }),
+            Arena::<Permanent>::try_new("body").map(|_| ())
+        );
+
+        let system = Arena::<Permanent>::new("System");
+
+        let mut body = Arena::<Permanent>::new("Body");
+        body.add_reference(&system);
+        assert_eq!(
+            Err(SchemaError::InvalidFieldName { arena: "Body".to_string(), field: "Radius".to_string() }),
+            body.try_add_required_component_with_field("Radius", "Length")
+        );
+        assert_eq!(
+            Err(SchemaError::DuplicateReference { arena: "Body".to_string(), field: "system".to_string() }),
+            body.try_add_many_reference(&system)
+        );
+
+        let mut orbit = Arena::<Permanent>::new("Orbit");
+        orbit.add_reference(&body);
+        orbit.set_referential_action("body", ReferentialAction::Cascade);
+
+        let surface = Arena::<Permanent>::new("Surface");
+        let mut planet = Entity::new(&body);
+        planet.add_child(&surface);
+        assert_eq!(
+            Err(SchemaError::DuplicateEntityArena { entity: "Body".to_string(), arena: "Surface".to_string() }),
+            planet.try_add_collection(&surface)
+        );
+        assert_eq!(
+            Err(SchemaError::InvalidEnumName { name: "body_kind".to_string() }),
+            EntityEnum::try_new("body_kind", vec![&surface]).map(|_| ())
+        );
+
+        let mut world = World::new();
+        world.insert(body);
+        assert_eq!(
+            Err(vec![SchemaError::DuplicateArena { arena: "Body".to_string() }]),
+            world.try_insert(Arena::<Permanent>::new("Body"))
+        );
+        assert_eq!(
+            Err(vec![SchemaError::PermanentCascade { arena: "Orbit".to_string(), field: "body".to_string() }]),
+            world.try_insert(orbit)
+        );
+        assert_eq!(
+            Err(vec![SchemaError::MissingEntityArena { entity: "Body".to_string(), arena: "Surface".to_string() }]),
+            world.try_insert(planet)
+        );
+        assert_eq!(
+            Err(SchemaError::UnknownArena { arena: "Surface".to_string() }),
+            world.try_get_id(&ArenaName::new("Surface"))
+        );
+
+        assert_eq!(
+            Err(vec![
+                SchemaError::MissingReferenceTarget {
+                    arena: "Body".to_string(),
+                    field: "system".to_string(),
+                    target: "System".to_string(),
+                },
+                SchemaError::DuplicateArena { arena: "Body".to_string() },
+                SchemaError::PermanentCascade { arena: "Orbit".to_string(), field: "body".to_string() },
+                SchemaError::MissingEntityArena { entity: "Body".to_string(), arena: "Surface".to_string() },
+            ]),
+            world.validate()
+        );
+
+        let generated = world.to_string();
+        assert_eq!(4, generated.lines().count());
+        assert!(generated.lines().all(|line| line.starts_with("compile_error!(")));
+    }
+
+    #[test]
+    fn field_collisions() {
+        let body = Arena::<Permanent>::new("Body");
+
+        let mut orbit = Arena::<Permanent>::new("Orbit");
+        orbit.add_required_component_with_field("body", "Length");
+
+        let mut colony = Arena::<Transient>::new("Colony");
+        colony.add_reference(&body);
+        colony.add_default_component_with_field("body", "String");
+
+        let mut planet = Entity::new(&body);
+        planet.add_child(&orbit);
+
+        let mut world = World::new();
+        world.insert(body);
+        world.insert(orbit);
+        world.insert(colony);
+        world.insert(planet);
+
+        let errors = world.validate().unwrap_err();
+
+        assert_eq!(
+            vec![
+                SchemaError::FieldCollision {
+                    arena: "Orbit".to_string(),
+                    field: "body".to_string(),
+                    origins: vec![
+                        FieldOrigin::Owner { entity: "Body".to_string() },
+                        FieldOrigin::Component,
+                    ],
+                },
+                SchemaError::FieldCollision {
+                    arena: "Colony".to_string(),
+                    field: "body".to_string(),
+                    origins: vec![
+                        FieldOrigin::Reference { target: "Body".to_string() },
+                        FieldOrigin::Component,
+                    ],
+                },
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn generate_field_collision() {
+        let body = Arena::<Permanent>::new("Body");
+
+        let mut orbit = Arena::<Permanent>::new("Orbit");
+        orbit.add_required_component_with_field("body", "Length");
+
+        let mut planet = Entity::new(&body);
+        planet.add_child(&orbit);
+
+        let mut world = World::new();
+        world.insert(body);
+        world.insert(orbit);
+        world.insert(planet);
+
+        let error = SchemaError::FieldCollision {
+            arena: "Orbit".to_string(),
+            field: "body".to_string(),
+            origins: vec![FieldOrigin::Owner { entity: "Body".to_string() }, FieldOrigin::Component],
+        };
+        assert_eq!(format!("compile_error!({:?});\n", error.to_string()), world.to_string());
+    }
+
+    #[test]
+    fn rename_reverse_index() {
+        let get_world = |reverse_index: Option<&str>| {
+            let mut body = Arena::<Permanent>::new("Body");
+            body.add_required_component_with_field("colony", "String");
+
+            let mut colony = Arena::<Transient>::new("Colony");
+            colony.add_reference(&body);
+            if let Some(name) = reverse_index {
+                colony.set_reverse_index("body", name);
+            }
+
+            let mut world = World::new();
+            world.insert(body);
+            world.insert(colony);
+            world
+        };
+
+        assert_eq!(
+            vec![SchemaError::FieldCollision {
+                arena: "Body".to_string(),
+                field: "colony".to_string(),
+                origins: vec![
+                    FieldOrigin::Component,
+                    FieldOrigin::ReverseIndex { referrer: "Colony".to_string(), field: "body".to_string() },
+                ],
+            }],
+            get_world(None).validate().unwrap_err()
+        );
+
+        let world = get_world(Some("colonies"));
+        assert!(world.validate().is_ok());
+        assert_eq!(get_type("Component<Self,Vec<GenId<Colony>>>"), get_field_type(&world, "Body", "colonies"));
+        assert!(has_function(&world.generate_state_impl(), "get_colony_by_body"));
+
+        let body = Arena::<Permanent>::new("Body");
+        let mut colony = Arena::<Transient>::new("Colony");
+        colony.add_reference(&body);
+        assert!(colony.try_set_reverse_index("body", "Colonies").is_err());
+        assert!(colony.try_set_reverse_index("nation", "colonies").is_err());
+    }
+
+    #[test]
+    fn entity_enum_transitions() {
+        let fleet = Arena::<Transient>::new("Fleet");
+        let orbit = Arena::<Transient>::new("FleetOrbit");
+        let transit = Arena::<Transient>::new("FleetTransit");
+
+        let mut entity = Entity::new(&fleet);
+        entity.add_enum(EntityEnum::new("FleetLocation", vec![&orbit, &transit]));
+
+        let mut world = World::new();
+        world.insert(fleet);
+        world.insert(orbit);
+        world.insert(transit);
+        world.insert(entity);
+
+        assert_eq!(get_type("Component<Self,FleetLocation>"), get_field_type(&world, "Fleet", "fleet_location"));
+        assert_eq!(get_type("Component<Self,GenId<Fleet>>"), get_field_type(&world, "FleetTransit", "fleet"));
+
+        let world_impl = world.generate_world_impl();
+        assert!(has_function(&world_impl, "set_fleet_location"));
+        assert!(has_function(&world_impl, "create_fleet_location"));
+        assert!(has_function(&world.generate_state_impl(), "link_fleet_to_fleet_transit"));
+    }
+
+    #[test]
+    fn permanent_entity_enums() {
+        let body = Arena::<Permanent>::new("Body");
+        let star = Arena::<Permanent>::new("Star");
+        let mut planet = Arena::<Permanent>::new("Planet");
+        planet.add_required_component("Albedo");
+
+        let mut entity = Entity::new(&body);
+        entity.add_enum(EntityEnum::new("BodyKind", vec![&star, &planet]));
+
+        let mut world = World::new();
+        world.insert(body);
+        world.insert(star);
+        world.insert(planet);
+        world.insert(entity);
+
+        assert_eq!(get_type("Component<Self,BodyKind>"), get_field_type(&world, "Body", "body_kind"));
+        assert!(has_function(&world.generate_state_impl(), "link_body_to_planet"));
+        assert!(!has_function(&world.generate_world_impl(), "set_body_kind"));
+    }
+
+    #[test]
+    fn attach_and_detach_children() {
+        let body = Arena::<Permanent>::new("Body");
+        let orbit = Arena::<Permanent>::new("Orbit");
+        let vessel = Arena::<Transient>::new("Vessel");
+        let engine = Arena::<Transient>::new("Engine");
+
+        let mut planet = Entity::new(&body);
+        planet.add_child(&orbit);
+
+        let mut ship = Entity::new(&vessel);
+        ship.add_child(&engine);
+
+        let mut world = World::new();
+        world.insert(body);
+        world.insert(orbit);
+        world.insert(vessel);
+        world.insert(engine);
+        world.insert(planet);
+        world.insert(ship);
+
+        let world_impl = world.generate_world_impl();
+
+        assert!(has_function(&world_impl, "attach_orbit_to_body"));
+        assert!(!has_function(&world_impl, "detach_orbit_from_body"));
+        assert!(has_function(&world_impl, "attach_engine_to_vessel"));
+        assert!(has_function(&world_impl, "detach_engine_from_vessel"));
+    }
+
+    #[test]
+    fn read_back_entities() {
+        let vessel = Arena::<Transient>::new("Vessel");
+        let mut engine = Arena::<Transient>::new("Engine");
+        engine.add_required_component_with_field("thrust", "Force");
+        engine.add_default_component("Temperature");
+
+        let mut ship = Entity::new(&vessel);
+        ship.add_child(&engine);
+
+        let mut world = World::new();
+        world.insert(vessel);
+        world.insert(engine);
+        world.insert(ship);
+
+        assert_eq!(vec!["thrust"], get_row_fields(&world, "Engine"));
+        assert!(has_function(&world.generate_state_impl(), "get_engine_row"));
+        assert!(has_function(&world.generate_world_impl(), "get_vessel"));
+    }
+
+    #[test]
+    fn row_views() {
+        let mut vessel = Arena::<Transient>::new("Vessel");
+        vessel.add_required_component("Mass");
+        vessel.add_optional_component_with_field("name", "String");
+
+        let mut world = World::new();
+        world.insert(vessel);
+
+        let views = world.generate_arena_row_views();
+        let names: Vec<_> = views.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(vec!["VesselRef", "VesselMut"], names);
+
+        let fields: Vec<_> = views[1].fields.iter().map(|(name, view_type)| (name.to_string(), view_type.as_str())).collect();
+        assert_eq!(
+            vec![("mass".to_string(), "&'a mut Mass"), ("name".to_string(), "&'a mut Option<String>")],
+            fields
+        );
+
+        let vessel = world.generate_arena_impl(world.get_arena(&ArenaName::new("Vessel")));
+        for f in &["get", "get_mut"] {
+            assert!(has_function(&vessel, f));
+        }
+    }
+
+    #[test]
+    fn iter_living_rows() {
+        let mut vessel = Arena::<Transient>::new("Vessel");
+        vessel.add_required_component("Speed");
+
+        let mut body = Arena::<Permanent>::new("Body");
+        body.add_required_component("Mass");
+
+        let mut world = World::new();
+        world.insert(vessel);
+        world.insert(body);
+
+        let vessel = world.generate_arena_impl(world.get_arena(&ArenaName::new("Vessel")));
+        assert!(has_function(&vessel, "iter_living"));
+        assert!(has_function(&vessel, "iter_living_mut"));
+        assert!(!has_function(&vessel, "iter"));
+        assert!(!has_function(&vessel, "iter_mut"));
+
+        let body = world.generate_arena_impl(world.get_arena(&ArenaName::new("Body")));
+        assert!(has_function(&body, "iter"));
+        assert!(has_function(&body, "iter_mut"));
+        assert!(!has_function(&body, "iter_living"));
+    }
+
+    #[test]
+    fn reference_traversals() {
+        let system = Arena::<Permanent>::new("System");
+
+        let mut body = Arena::<Permanent>::new("Body");
+        body.add_reference(&system);
+        body.add_required_component("Mass");
+
+        let nation = Arena::<Transient>::new("Nation");
+
+        let mut colony = Arena::<Transient>::new("Colony");
+        colony.add_reference(&body);
+        colony.add_optional_reference_with_field("port", &body);
+        colony.add_owner_reference(&nation);
+
+        let mut world = World::new();
+        world.insert(system);
+        world.insert(body);
+        world.insert(nation);
+        world.insert(colony);
+
+        let state = world.generate_state_impl();
+
+        assert!(has_function(&state, "colony_body_system"));
+        assert!(has_function(&state, "colony_port_system"));
+        assert!(has_function(&state, "iter_colony_with_body"));
+        assert!(has_function(&state, "iter_colony_with_port"));
+        assert!(!has_function(&state, "iter_colony_with_nation"));
+        assert!(!has_function(&state, "iter_body_with_system"));
+    }
+
+    #[test]
+    fn reverse_indexes() {
+        let system = Arena::<Permanent>::new("System");
+
+        let mut body = Arena::<Permanent>::new("Body");
+        body.add_reference(&system);
+
+        let mut colony = Arena::<Transient>::new("Colony");
+        colony.add_reference(&body);
+        colony.add_optional_reference_with_field("port", &body);
+
+        let mut world = World::new();
+        world.insert(system);
+        world.insert(body);
+        world.insert(colony);
+
+        assert_eq!(get_type("Component<Self,Vec<Id<Body>>>"), get_field_type(&world, "System", "body"));
+        assert_eq!(get_type("Component<Self,Vec<GenId<Colony>>>"), get_field_type(&world, "Body", "colony"));
+        assert_eq!(get_type("Component<Self,Vec<GenId<Colony>>>"), get_field_type(&world, "Body", "colony_port"));
+
+        let state = world.generate_state_impl();
+        for f in &["index_body", "unindex_colony", "set_colony_port", "get_colony_by_body", "get_colony_by_port"] {
+            assert!(has_function(&state, f));
+        }
+        assert!(!has_function(&state, "can_delete_body"));
+    }
+
+    #[test]
+    fn many_references() {
+        let system = Arena::<Permanent>::new("System");
+        let colony = Arena::<Transient>::new("Colony");
+
+        let mut trade_route = Arena::<Transient>::new("TradeRoute");
+        trade_route.add_many_reference_with_field("colonies", &colony);
+        trade_route.add_many_reference(&system);
+
+        let mut world = World::new();
+        world.insert(system);
+        world.insert(colony);
+        world.insert(trade_route);
+
+        assert_eq!(Ok(()), world.validate());
+
+        assert_eq!(get_type("Component<Self,Vec<GenId<Colony>>>"), get_field_type(&world, "TradeRoute", "colonies"));
+        assert_eq!(get_type("Component<Self,Vec<GenId<TradeRoute>>>"), get_field_type(&world, "Colony", "trade_route_colonies"));
+        assert_eq!(get_type("Component<Self,Vec<GenId<TradeRoute>>>"), get_field_type(&world, "System", "trade_route"));
+
+        let state = world.generate_state_impl();
+        for f in &["link_trade_route_colonies", "unlink_trade_route_system", "iter_colony_trade_route_colonies", "prune_colony_links"] {
+            assert!(has_function(&state, f));
+        }
+        assert!(!has_function(&state, "prune_system_links"));
+    }
+
+    #[test]
+    fn self_link_hierarchy() {
+        let mut orbit = Arena::<Permanent>::new("Orbit");
+        orbit.add_optional_self_link("parent");
+
+        let mut leader = Arena::<Transient>::new("Leader");
+        leader.add_optional_self_link("mentor");
+
+        let mut world = World::new();
+        world.insert(orbit);
+        world.insert(leader);
+
+        assert_eq!(get_type("Component<Self,Vec<GenId<Leader>>>"), get_field_type(&world, "Leader", "leader_mentor"));
+
+        let state = world.generate_state_impl();
+        for f in &["set_orbit_parent", "get_orbit_by_parent", "iter_orbit_ancestors_by_parent", "iter_leader_hierarchy_by_mentor"] {
+            assert!(has_function(&state, f));
+        }
+    }
+
+    #[test]
+    fn rare_components() {
+        let mut vessel = Arena::<Transient>::new("Vessel");
+        vessel.add_required_component("Mass");
+        vessel.add_rare_component("Atmosphere");
+
+        let mut world = World::new();
+        world.insert(vessel);
+
+        assert_eq!(get_type("ComponentMap<GenId<Vessel>,Atmosphere>"), get_field_type(&world, "Vessel", "atmosphere"));
+
+        let row = world.generate_arena_row(world.get_arena(&ArenaName::new("Vessel")));
+        let atmosphere = row.fields.iter().find(|f| f.name.to_string() == "atmosphere").unwrap();
+        assert_eq!(Type::new("Option<Atmosphere>").to_string(), atmosphere.field_type.to_string());
+
+        let views = world.generate_arena_row_views();
+        assert!(views.iter().all(|v| v.fields.iter().all(|(name, _)| name.to_string() != "atmosphere")));
+    }
+
+    #[test]
+    fn tag_components() {
+        let mut colony = Arena::<Transient>::new("Colony");
+        colony.add_required_component("Population");
+        colony.add_tag("is_capital");
+        colony.add_required_component_with_field("is_capital", "bool");
+
+        let mut world = World::new();
+        world.insert(colony);
+
+        assert_eq!(
+            Err(vec![SchemaError::FieldCollision {
+                arena: "Colony".to_string(),
+                field: "is_capital".to_string(),
+                origins: vec![FieldOrigin::Component, FieldOrigin::Tag],
+            }]),
+            world.validate()
+        );
+
+        let mut colony = Arena::<Transient>::new("Colony");
+        colony.add_tag("is_capital");
+
+        let mut world = World::new();
+        world.insert(colony);
+
+        assert_eq!(get_type("TagComponent<GenId<Colony>>"), get_field_type(&world, "Colony", "is_capital"));
+        assert!(get_row_fields(&world, "Colony").is_empty());
+    }
+
+    #[test]
+    fn default_expressions() {
+        let mut surface = Arena::<Permanent>::new("Surface");
+        surface.add_required_component("Albedo");
+        surface.add_default_component_with_expr("temperature", "Temperature", "Temperature::in_kelvin(3.0)");
+        surface.add_default_component_with_init("reflectance", "f64", "|row| row.albedo.0");
+
+        let mut world = World::new();
+        world.insert(surface);
+
+        assert_eq!(get_type("Component<Self,Temperature>"), get_field_type(&world, "Surface", "temperature"));
+        assert_eq!(get_type("Component<Self,f64>"), get_field_type(&world, "Surface", "reflectance"));
+        assert_eq!(vec!["albedo"], get_row_fields(&world, "Surface"));
+    }
+
+    #[test]
+    fn derived_components() {
+        let mut orbit = Arena::<Permanent>::new("Orbit");
+        orbit.add_required_component_with_field("period", "Time");
+        orbit.add_required_component_with_field("radius", "Length");
+        orbit.add_derived_component_with_field("speed", "Speed", "get_speed", &["period", "radius"]);
+        orbit.add_derived_component("Angle", "get_angle", &[]);
+
+        assert_eq!(
+            Err(SchemaError::InvalidDerivedInput {
+                arena: "Orbit".to_string(),
+                field: "mass".to_string(),
+                input: "density".to_string(),
+            }),
+            orbit.try_add_derived_component_with_field("mass", "Mass", "get_mass", &["density"])
+        );
+
+        let mut world = World::new();
+        world.insert(orbit);
+
+        assert_eq!(get_type("Component<Self,Speed>"), get_field_type(&world, "Orbit", "speed"));
+        assert_eq!(vec!["period", "radius"], get_row_fields(&world, "Orbit"));
+
+        let state = world.generate_state_impl();
+        assert!(has_function(&state, "update_orbit_speed"));
+        assert!(has_function(&state, "update_orbit_angle"));
+        assert!(has_function(&state, "update_orbit"));
+    }
+
+    #[test]
+    fn linked_derived_components() {
+        let mut body = Arena::<Permanent>::new("Body");
+        body.add_derived_component("Position", "get_body_position", &["orbit.position"]);
+
+        let mut orbit = Arena::<Permanent>::new("Orbit");
+        orbit.add_optional_self_link("parent");
+        orbit.add_default_component_with_field("relative_position", "Position");
+        orbit.add_derived_component("Position", "get_orbit_position", &["relative_position", "parent.position"]);
+
+        let mut planet = Entity::new(&body);
+        planet.add_child(&orbit);
+
+        let mut world = World::new();
+        world.insert(body);
+        world.insert(orbit);
+        world.insert(planet);
+
+        assert_eq!(Ok(()), world.validate());
+        assert!(get_row_fields(&world, "Body").is_empty());
+
+        let state = world.generate_state_impl();
+        assert!(has_function(&state, "update_body_position"));
+        assert!(has_function(&state, "update_orbit_position"));
+        assert!(has_function(&world.generate_world_impl(), "update_derived"));
+
+        let body = world.generate_arena_impl(world.get_arena(&ArenaName::new("Body")));
+        assert!(has_function(&body, "get"));
+        assert!(!has_function(&body, "get_mut"));
+        assert!(!has_function(&body, "iter_mut"));
+    }
+
+    #[test]
+    fn linked_derived_input_errors() {
+        let mut body = Arena::<Permanent>::new("Body");
+        body.add_derived_component_with_field("heat", "Temperature", "get_heat", &["orbit.temperature"]);
+        body.add_derived_component("Mass", "get_mass", &["star.mass"]);
+        assert!(body.try_add_derived_component("Radius", "get_radius", &["Star.radius"]).is_err());
+
+        let mut orbit = Arena::<Permanent>::new("Orbit");
+        orbit.add_derived_component("Temperature", "get_temperature", &["body.heat"]);
+
+        let mut planet = Entity::new(&body);
+        planet.add_child(&orbit);
+
+        let mut world = World::new();
+        world.insert(body);
+        world.insert(orbit);
+        world.insert(planet);
+
+        assert_eq!(
+            Err(vec![
+                SchemaError::InvalidDerivedInput {
+                    arena: "Body".to_string(),
+                    field: "mass".to_string(),
+                    input: "star.mass".to_string(),
+                },
+                SchemaError::DerivedCycle { arena: "Body".to_string() },
+            ]),
+            world.validate()
+        );
+    }
+
+    // the type of a generated arena struct field, as code_gen prints it
+    fn get_field_type(world: &World, arena: &str, field: &str) -> Option<String> {
+        world
+            .generate_arena(world.get_arena(&ArenaName::new(arena)))
+            .fields
+            .iter()
+            .find(|f| f.name.to_string() == field)
+            .map(|f| f.field_type.to_string())
+    }
+
+    fn get_type(type_name: &str) -> Option<String> {
+        Some(Type::new(type_name).to_string())
+    }
+
+    fn get_row_fields(world: &World, arena: &str) -> Vec<String> {
+        world
+            .generate_arena_row(world.get_arena(&ArenaName::new(arena)))
+            .fields
+            .iter()
+            .map(|f| f.name.to_string())
+            .collect()
+    }
+
+    fn has_function(code: &impl Display, name: &str) -> bool {
+        let code = code.to_string();
+        code.contains(&format!("fn {}(", name)) || code.contains(&format!("fn {}<", name))
+    }
+
     pub fn get_world() -> World {
         Default::default()
 
@@ -0,0 +1,361 @@
+// Compiles the world generated by examples/gen.rs and exercises it at runtime.
+#![allow(dead_code)]
+
+include!("../examples/target.rs");
+
+#[path = "../examples/gen.rs"]
+mod gen;
+
+#[test]
+fn target_is_generated() {
+    let target = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/examples/target.rs")).unwrap();
+
+    assert!(
+        gen::generate() == target,
+        "examples/target.rs is out of date, run `cargo run --example gen`"
+    );
+}
+
+fn create_earth(world: &mut World) -> Id<Body> {
+    let sol = world.create_system(get_sol());
+    world.create_body(get_earth(sol))
+}
+
+fn create_nation(world: &mut World) -> GenId<Nation> {
+    world
+        .create_nation(NationRow {
+            name: "Terra".to_string(),
+            flag: Flag('T'),
+        })
+        .id()
+}
+
+fn create_colony(world: &mut World, body: Id<Body>, nation: GenId<Nation>, name: &str) -> GenId<Colony> {
+    world
+        .create_colony(ColonyRow {
+            body,
+            nation,
+            name: name.to_string(),
+            population: Population,
+            supply_pod: None,
+        })
+        .id()
+}
+
+fn get_vessel_entity(body: Id<Body>) -> VesselEntity {
+    VesselEntity {
+        vessel: VesselRow {
+            name: "Endurance".to_string(),
+            mass: Mass::in_kilograms(5e5),
+            speed: Default::default(),
+        },
+        engine: Some(EngineRow {
+            thrust: Default::default(),
+        }),
+        cargo_pod: vec![
+            CargoPodRow {
+                cargo: Mass::in_kilograms(1e3),
+            },
+            CargoPodRow {
+                cargo: Mass::in_kilograms(2e3),
+            },
+        ],
+        vessel_location: VesselOrbitRow {
+            parent: Some(body),
+            period: Time::in_days(1.0),
+        }
+        .into(),
+    }
+}
+
+#[test]
+fn create_and_read_back_bodies() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+    let luna = world.create_body(get_luna(&world.state, earth));
+
+    let entity = world.get_body(luna);
+    assert_eq!(Some("Luna".to_string()), entity.body.name);
+    assert!(entity.orbit.is_some());
+    assert!(entity.surface.is_some());
+    assert_eq!(1, world.get_body(earth).mine_site.len());
+
+    let earth_orbit = world.state.body.orbit[&earth].unwrap();
+    let luna_orbit = world.state.body.orbit[&luna].unwrap();
+    assert_eq!(
+        vec![earth_orbit],
+        world.state.iter_orbit_ancestors_by_parent(&luna_orbit).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![earth_orbit, luna_orbit],
+        world.state.iter_orbit_hierarchy_by_parent(&world.allocators.orbit).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn delete_cascades_to_owned_rows() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+
+    let nation = create_nation(&mut world);
+    let colony = create_colony(&mut world, earth, nation, "Houston");
+
+    let route = world.create_trade_route(TradeRouteRow { distance: Length::in_meters(1e3) }).id();
+    let (alloc, state) = world.split();
+    state.link_trade_route_colonies(
+        &alloc.trade_route.validate(route).unwrap(),
+        &alloc.colony.validate(colony).unwrap(),
+    );
+
+    assert_eq!(Flag('T'), world.state.nation.get(&world.allocators.nation.validate(nation).unwrap()).flag.clone());
+    world.delete_nation(nation).unwrap();
+
+    assert!(world.allocators.nation.validate(nation).is_none());
+    assert!(world.allocators.colony.validate(colony).is_none());
+    assert!(world.state.get_colony_by_body(&earth).is_empty());
+
+    let route = world.allocators.trade_route.validate(route).unwrap();
+    assert_eq!(0, world.state.iter_trade_route_colonies(&route).count());
+}
+
+#[test]
+fn delete_entity_kills_children() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+
+    let vessel = world.create_vessel(get_vessel_entity(earth)).id();
+    let valid = world.allocators.vessel.validate(vessel).unwrap();
+    let engine = world.state.vessel.engine[&valid].unwrap();
+    let pods = world.state.vessel.cargo_pod[&valid].clone();
+    let location = world.state.vessel.vessel_location[&valid];
+    assert_eq!(2, pods.len());
+    assert_eq!(1, world.state.get_vessel_orbit_by_parent(&earth).len());
+
+    world.delete_vessel(vessel).unwrap();
+
+    assert!(world.get_vessel(vessel).is_none());
+    assert!(world.allocators.engine.validate(engine).is_none());
+    assert!(pods.iter().all(|pod| world.allocators.cargo_pod.validate(*pod).is_none()));
+    match location {
+        VesselLocation::VesselOrbit(orbit) => assert!(world.allocators.vessel_orbit.validate(orbit).is_none()),
+        VesselLocation::VesselTransit(_) => panic!("vessel was created in orbit"),
+    }
+    assert!(world.state.get_vessel_orbit_by_parent(&earth).is_empty());
+
+    let id = Valid::new(vessel);
+    assert!(world.state.vessel.name[&id].is_empty());
+    assert_eq!(Registry::default(), world.state.vessel.registry[&id]);
+}
+
+#[test]
+fn replace_entity_children() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+    let vessel = world.create_vessel(get_vessel_entity(earth)).id();
+
+    let engine = world.detach_engine_from_vessel(vessel).unwrap().unwrap();
+    assert!(world.allocators.engine.validate(engine).is_none());
+    assert!(world.get_vessel(vessel).unwrap().engine.is_none());
+
+    let engine = world.attach_engine_to_vessel(vessel, EngineRow { thrust: Default::default() }).unwrap().id();
+    assert!(world.allocators.engine.validate(engine).is_some());
+
+    let transit = VesselTransitRow {
+        departure: Time::in_days(0.0),
+        arrival: Time::in_days(3.0),
+        from: earth,
+        to: earth,
+    };
+    let old = world.get_vessel(vessel).unwrap().vessel_location;
+    assert!(matches!(old, VesselLocationRow::VesselOrbit(_)));
+
+    assert!(world.create_vessel_location(vessel, transit.clone().into()).is_none());
+    assert!(world.state.get_vessel_transit_by_from(&earth).is_empty());
+
+    world.set_vessel_location(vessel, transit.into()).unwrap();
+    assert!(world.state.get_vessel_orbit_by_parent(&earth).is_empty());
+    assert_eq!(1, world.state.get_vessel_transit_by_from(&earth).len());
+    assert!(matches!(world.get_vessel(vessel).unwrap().vessel_location, VesselLocationRow::VesselTransit(_)));
+}
+
+#[test]
+fn tags_follow_rows() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+    let nation = create_nation(&mut world);
+
+    let capital = create_colony(&mut world, earth, nation, "Houston");
+    let valid = world.allocators.colony.validate(capital).unwrap();
+    world.state.colony.is_capital.set(&valid);
+    assert!(world.state.colony.is_capital.contains(&valid));
+
+    world.delete_colony(capital).unwrap();
+    let colony = create_colony(&mut world, earth, nation, "Boca Chica");
+    let valid = world.allocators.colony.validate(colony).unwrap();
+    assert!(!world.state.colony.is_capital.contains(&valid));
+
+    // killing the id directly leaves the bit behind until the slot is reused
+    world.state.colony.is_capital.set(&valid);
+    world.allocators.colony.kill(colony);
+    assert_eq!(0, world.state.colony.is_capital.iter_living(&world.allocators.colony).count());
+
+    let colony = create_colony(&mut world, earth, nation, "Kourou");
+    let valid = world.allocators.colony.validate(colony).unwrap();
+    assert!(!world.state.colony.is_capital.contains(&valid));
+}
+
+#[test]
+fn init_transient_component_from_row() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+
+    let first = world.create_vessel(get_vessel_entity(earth)).id();
+    let mut entity = get_vessel_entity(earth);
+    entity.vessel.name = "Resolute".to_string();
+    world.delete_vessel(first).unwrap();
+    let second = world.create_vessel(entity).id();
+
+    let valid = world.allocators.vessel.validate(second).unwrap();
+    assert_eq!(Registry("RESOLUTE".to_string()), world.state.vessel.registry[&valid]);
+}
+
+#[test]
+fn delete_entity_applies_child_referential_actions() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+    let nation = create_nation(&mut world);
+
+    let vessel = world.create_vessel(get_vessel_entity(earth)).id();
+    let valid = world.allocators.vessel.validate(vessel).unwrap();
+    let pod = world.state.vessel.cargo_pod[&valid][0];
+    let engine = world.state.vessel.engine[&valid].unwrap();
+
+    let colony = world
+        .create_colony(ColonyRow {
+            body: earth,
+            nation,
+            name: "Houston".to_string(),
+            population: Population,
+            supply_pod: Some(pod),
+        })
+        .id();
+    let contract = world.create_contract(ContractRow { pod, payment: 1.0 }).id();
+    let order = world.create_repair_order(RepairOrderRow { engine: Some(engine) }).id();
+
+    assert!(world.delete_vessel(vessel).is_err());
+    assert!(world.detach_engine_from_vessel(vessel).is_err());
+    assert!(world.allocators.engine.validate(engine).is_some());
+    assert!(world.allocators.cargo_pod.validate(pod).is_some());
+
+    world.delete_repair_order(order).unwrap();
+    world.delete_vessel(vessel).unwrap();
+
+    assert!(world.allocators.cargo_pod.validate(pod).is_none());
+    assert!(world.allocators.contract.validate(contract).is_none());
+    let colony = world.allocators.colony.validate(colony).unwrap();
+    assert!(world.state.colony.supply_pod[&colony].is_none());
+}
+
+#[test]
+fn detach_child_is_restricted_by_referrers() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+
+    let vessel = world.create_vessel(get_vessel_entity(earth)).id();
+    let valid = world.allocators.vessel.validate(vessel).unwrap();
+    let engine = world.state.vessel.engine[&valid].unwrap();
+    let order = world.create_repair_order(RepairOrderRow { engine: Some(engine) }).id();
+
+    assert!(world.detach_engine_from_vessel(vessel).is_err());
+    assert!(world.get_vessel(vessel).unwrap().engine.is_some());
+
+    world.delete_repair_order(order).unwrap();
+    assert_eq!(Some(engine), world.detach_engine_from_vessel(vessel).unwrap());
+    assert_eq!(None, world.detach_engine_from_vessel(vessel).unwrap());
+}
+
+#[test]
+fn remove_collection_child_deletes_it() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+
+    let vessel = world.create_vessel(get_vessel_entity(earth)).id();
+    let valid = world.allocators.vessel.validate(vessel).unwrap();
+    let pods = world.state.vessel.cargo_pod[&valid].clone();
+    let contract = world.create_contract(ContractRow { pod: pods[0], payment: 1.0 }).id();
+
+    assert_eq!(Some(pods[0]), world.remove_cargo_pod_from_vessel(vessel, pods[0]).unwrap());
+    assert_eq!(None, world.remove_cargo_pod_from_vessel(vessel, pods[0]).unwrap());
+
+    assert!(world.allocators.cargo_pod.validate(pods[0]).is_none());
+    assert!(world.allocators.contract.validate(contract).is_none());
+    assert_eq!(1, world.get_vessel(vessel).unwrap().cargo_pod.len());
+}
+
+#[test]
+fn late_reference_is_nullified_with_its_target() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+    let nation = create_nation(&mut world);
+    let colony = create_colony(&mut world, earth, nation, "Houston");
+
+    let vessel = world.create_vessel(get_vessel_entity(earth)).id();
+    let (alloc, state) = world.split();
+    let valid = alloc.vessel.validate(vessel).unwrap();
+    assert!(state.vessel.home[&valid].is_none());
+
+    state.set_vessel_home(&valid, colony);
+    assert_eq!(Some(colony), state.vessel.home[&valid]);
+    assert_eq!(&[vessel], state.get_vessel_by_home(&alloc.colony.validate(colony).unwrap()));
+
+    state.clear_vessel_home(&valid);
+    assert!(state.vessel.home[&valid].is_none());
+    assert!(state.get_vessel_by_home(&alloc.colony.validate(colony).unwrap()).is_empty());
+
+    state.set_vessel_home(&valid, colony);
+    world.delete_colony(colony).unwrap();
+
+    let valid = world.allocators.vessel.validate(vessel).unwrap();
+    assert!(world.state.vessel.home[&valid].is_none());
+}
+
+#[test]
+fn hierarchy_walks_stop_on_a_written_cycle() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+    let luna = world.create_body(get_luna(&world.state, earth));
+
+    let earth_orbit = world.state.body.orbit[&earth].unwrap();
+    let luna_orbit = world.state.body.orbit[&luna].unwrap();
+
+    world.state.orbit.orbit_parent[&luna_orbit].push(earth_orbit);
+    assert_eq!(2, world.state.iter_orbit_hierarchy_by_parent(&world.allocators.orbit).count());
+
+    world.state.orbit.parent[&earth_orbit] = Some(luna_orbit);
+    assert_eq!(2, world.state.iter_orbit_ancestors_by_parent(&luna_orbit).count());
+}
+
+#[test]
+fn update_derived_follows_the_orbit_hierarchy() {
+    let mut world = World::default();
+    let earth = create_earth(&mut world);
+    let luna = world.create_body(get_luna(&world.state, earth));
+    let station = world.create_body(get_luna(&world.state, earth));
+
+    let earth_orbit = world.state.body.orbit[&earth].unwrap();
+    let luna_orbit = world.state.body.orbit[&luna].unwrap();
+    let station_orbit = world.state.body.orbit[&station].unwrap();
+
+    // luna now orbits a parent that comes after it in the arena
+    world.state.set_orbit_parent(&luna_orbit, Some(station_orbit)).unwrap();
+    world.update_derived();
+
+    assert_eq!(0, world.state.orbit.depth[&earth_orbit]);
+    assert_eq!(1, world.state.orbit.depth[&station_orbit]);
+    assert_eq!(2, world.state.orbit.depth[&luna_orbit]);
+
+    assert_eq!(
+        format!("{:?}", world.state.orbit.position[&luna_orbit]),
+        format!("{:?}", world.state.body.position[&luna])
+    );
+}